@@ -0,0 +1,275 @@
+//! Use an external engine, running as a subprocess, as a [Bot](crate::ai::Bot).
+//!
+//! This allows the bots in this crate to be compared against third-party engines, for example with
+//! [bot_game](crate::util::bot_game). [ExternalBot] requires the `native` feature.
+#[cfg(feature = "native")]
+pub use bot::ExternalBot;
+
+use crate::board::Board;
+
+/// The protocol spoken by the external engine.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Protocol {
+    /// The Universal Ataxx Interface.
+    Uai,
+    /// The Universal Chess Interface.
+    Uci,
+}
+
+/// The game-specific notation used to talk to external engines and frontends,
+/// both by [ExternalBot] and by the [UCI engine loop](crate::uci::engine).
+pub trait ExternalBoard: Board {
+    /// The board for `position startpos`.
+    fn external_start_pos() -> Self;
+
+    /// The position in the FEN notation used by UAI and UCI.
+    fn external_fen(&self) -> String;
+
+    /// Parse a position in the FEN notation, returns `None` if `fen` is not valid.
+    fn external_from_fen(fen: &str) -> Option<Self>;
+
+    /// Convert a move to the notation used by the protocol.
+    fn external_format_move(&self, mv: Self::Move) -> String;
+
+    /// Parse a move in the notation used by the protocol, returns `None` if `s` is not a valid move notation.
+    /// The move is not required to be available on this board.
+    fn external_parse_move(&self, s: &str) -> Option<Self::Move>;
+}
+
+#[cfg(feature = "native")]
+mod bot {
+    use std::fmt::{Debug, Formatter};
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+    use std::time::{Duration, Instant};
+
+    use internal_iterator::InternalIterator;
+
+    use crate::ai::external::{ExternalBoard, Protocol};
+    use crate::ai::Bot;
+
+    /// A [Bot] that asks an external engine for each move.
+    ///
+    /// Each call to [select_move](Bot::select_move) sends the position to the engine and waits for the answer
+    /// for at most `move_time + timeout`. Consecutive positions of the same game are sent as the start of the game
+    /// followed by the moves played since, so the engine can detect repetitions. A misbehaving engine causes a panic, use
+    /// [try_select_move](ExternalBot::try_select_move) to handle errors instead.
+    pub struct ExternalBot {
+        protocol: Protocol,
+        move_time: Duration,
+        timeout: Duration,
+
+        child: Child,
+        stdin: ChildStdin,
+        stdout: Receiver<String>,
+
+        game: Option<GameHistory>,
+    }
+
+    /// The game the engine is currently playing, in the notation of the protocol.
+    struct GameHistory {
+        start: String,
+        moves: Vec<String>,
+        // the position after the last move
+        current: String,
+    }
+
+    impl Debug for ExternalBot {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "ExternalBot {{ protocol: {:?}, move_time: {:?}, pid: {} }}",
+                self.protocol,
+                self.move_time,
+                self.child.id()
+            )
+        }
+    }
+
+    impl ExternalBot {
+        /// Spawn the engine with `command` and perform the initial handshake.
+        ///
+        /// The engine is given `move_time` to think for each move, `timeout` is the extra time allowed
+        /// for the engine to respond before it is considered unresponsive.
+        pub fn new(
+            mut command: Command,
+            protocol: Protocol,
+            move_time: Duration,
+            timeout: Duration,
+        ) -> Result<Self, String> {
+            let mut child = command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Failed to spawn engine: {}", e))?;
+
+            let stdin = child.stdin.take().unwrap();
+            let stdout = child.stdout.take().unwrap();
+
+            // read on a separate thread so we can apply timeouts
+            let (sender, receiver) = channel();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break,
+                    };
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let mut bot = ExternalBot {
+                protocol,
+                move_time,
+                timeout,
+                child,
+                stdin,
+                stdout: receiver,
+                game: None,
+            };
+
+            let name = bot.protocol_name();
+            bot.send(name)?;
+            bot.wait_for(timeout, |line| line == format!("{}ok", name))?;
+            bot.send(&format!("{}newgame", name))?;
+            bot.send("isready")?;
+            bot.wait_for(timeout, |line| line == "readyok")?;
+
+            Ok(bot)
+        }
+
+        fn protocol_name(&self) -> &'static str {
+            match self.protocol {
+                Protocol::Uai => "uai",
+                Protocol::Uci => "uci",
+            }
+        }
+
+        /// Ask the engine for a move, returning an error if the engine does not respond in time
+        /// or responds with an invalid move.
+        pub fn try_select_move<B: ExternalBoard>(&mut self, board: &B) -> Result<B::Move, String> {
+            assert!(!board.is_done(), "Cannot select move on a finished board");
+
+            let wait_time = self.move_time + self.timeout;
+
+            self.update_game(board)?;
+            let game = self.game.as_ref().unwrap();
+            let mut position = format!("position fen {}", game.start);
+            if !game.moves.is_empty() {
+                position.push_str(" moves ");
+                position.push_str(&game.moves.join(" "));
+            }
+            self.send(&position)?;
+            self.send(&format!("go movetime {}", self.move_time.as_millis()))?;
+
+            let line = self.wait_for(wait_time, |line| line.starts_with("bestmove"))?;
+            let mv_str = match line.split_whitespace().nth(1) {
+                Some(mv_str) => mv_str.to_string(),
+                None => return Err(format!("Invalid response '{}'", line)),
+            };
+
+            let mv = board
+                .external_parse_move(&mv_str)
+                .filter(|&mv| board.is_available_move(mv))
+                .ok_or_else(|| format!("Engine played invalid move '{}' on board {:?}", mv_str, board))?;
+
+            let game = self.game.as_mut().unwrap();
+            game.moves.push(mv_str);
+            game.current = board.clone_and_play(mv).external_fen();
+
+            Ok(mv)
+        }
+
+        /// Extend the game history to end at `board`, which is the same position or the position after one move.
+        /// Otherwise a new game is started from `board`.
+        fn update_game<B: ExternalBoard>(&mut self, board: &B) -> Result<(), String> {
+            let fen = board.external_fen();
+
+            if let Some(game) = &mut self.game {
+                if game.current == fen {
+                    return Ok(());
+                }
+                let prev = B::external_from_fen(&game.current).filter(|prev| !prev.is_done());
+                let mv = prev.as_ref().and_then(|prev| {
+                    prev.available_moves()
+                        .find(|&mv| prev.clone_and_play(mv).external_fen() == fen)
+                });
+                if let (Some(prev), Some(mv)) = (prev, mv) {
+                    game.moves.push(prev.external_format_move(mv));
+                    game.current = fen;
+                    return Ok(());
+                }
+
+                // this is a different game
+                let name = self.protocol_name();
+                self.send(&format!("{}newgame", name))?;
+            }
+
+            self.game = Some(GameHistory {
+                start: fen.clone(),
+                moves: vec![],
+                current: fen,
+            });
+            Ok(())
+        }
+
+        fn send(&mut self, line: &str) -> Result<(), String> {
+            writeln!(self.stdin, "{}", line)
+                .and_then(|_| self.stdin.flush())
+                .map_err(|e| format!("Failed to write to engine: {}", e))
+        }
+
+        /// Wait for a line for which `f` returns true, skipping all other lines.
+        fn wait_for(&mut self, time: Duration, mut f: impl FnMut(&str) -> bool) -> Result<String, String> {
+            let deadline = Instant::now() + time;
+
+            loop {
+                let left = deadline.saturating_duration_since(Instant::now());
+                match self.stdout.recv_timeout(left) {
+                    Ok(line) => {
+                        if f(line.trim()) {
+                            return Ok(line.trim().to_string());
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => return Err("Engine did not respond in time".to_string()),
+                    Err(RecvTimeoutError::Disconnected) => return Err("Engine closed its output".to_string()),
+                }
+            }
+        }
+    }
+
+    impl<B: ExternalBoard> Bot<B> for ExternalBot {
+        fn select_move(&mut self, board: &B) -> B::Move {
+            self.try_select_move(board).unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Asks the engine to use `time` for this move instead of the move time of this bot.
+        fn select_move_timed(&mut self, board: &B, time: Duration) -> (B::Move, Option<f32>) {
+            let move_time = std::mem::replace(&mut self.move_time, time);
+            let result = self.try_select_move(board);
+            self.move_time = move_time;
+            (result.unwrap_or_else(|e| panic!("{}", e)), None)
+        }
+    }
+
+    impl Drop for ExternalBot {
+        fn drop(&mut self) {
+            // ask the engine to quit, and kill it if it doesn't listen
+            let _ = self.send("quit");
+
+            let deadline = Instant::now() + self.timeout;
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = self.child.try_wait() {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
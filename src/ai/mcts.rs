@@ -1,6 +1,9 @@
 use std::fmt::{Debug, Formatter};
+use std::mem::size_of;
 use std::num::NonZeroUsize;
 use std::ops::{Index, IndexMut};
+#[cfg(feature = "native")]
+use std::time::{Duration, Instant};
 
 use decorum::N32;
 use internal_iterator::InternalIterator;
@@ -9,6 +12,7 @@ use rand::Rng;
 
 use crate::ai::Bot;
 use crate::board::{Board, Outcome};
+use crate::util::arena::Arena;
 use crate::wdl::{Flip, OutcomeWDL, POV, WDL};
 
 #[derive(Debug, Copy, Clone)]
@@ -139,11 +143,13 @@ impl<M> Node<M> {
     }
 }
 
-/// A small wrapper type for Vec<SNode> that uses u64 for indexing instead.
+/// The search tree, the root node is at index `0`.
+///
+/// The nodes are stored in an [Arena] so large trees can keep growing without copying all existing nodes.
 #[derive(Debug)]
 pub struct Tree<B: Board> {
     pub root_board: B,
-    pub nodes: Vec<Node<B::Move>>,
+    pub nodes: Arena<Node<B::Move>>,
 }
 
 impl<B: Board> Tree<B> {
@@ -154,6 +160,28 @@ impl<B: Board> Tree<B> {
         }
     }
 
+    /// A tree that only allocates as many nodes as fit in `memory_limit` bytes, see [Tree::memory_bytes].
+    pub fn with_memory_limit(root_board: B, memory_limit: usize) -> Self {
+        let max_nodes = memory_limit.saturating_sub(size_of::<Self>()) / size_of::<Node<B::Move>>();
+        Tree {
+            root_board,
+            nodes: Arena::with_max_len(max_nodes),
+        }
+    }
+
+    /// The number of bytes allocated for this tree, not counting heap memory owned by `root_board`.
+    pub fn memory_bytes(&self) -> usize {
+        size_of::<Self>() + self.nodes.capacity() * size_of::<Node<B::Move>>()
+    }
+
+    /// Whether `count` more nodes fit in the memory limit of this tree.
+    fn can_grow(&self, count: usize) -> bool {
+        match self.nodes.max_len() {
+            None => true,
+            Some(max_len) => self.nodes.len() + count <= max_len,
+        }
+    }
+
     pub fn best_child(&self) -> usize {
         let children = self[0].children.expect("Root node must have children");
 
@@ -253,6 +281,9 @@ fn random_playout<B: Board>(mut board: B, rng: &mut impl Rng) -> Outcome {
 /// * `proven` is whether this result is fully proven
 ///
 /// This function has already increments `curr_node` before it returns.
+///
+/// If expanding `curr_node` would make the tree exceed its memory limit it stays a leaf,
+/// and a random playout is run from it instead. The root node is always expanded.
 fn mcts_solver_step<B: Board>(
     tree: &mut Tree<B>,
     curr_node: usize,
@@ -271,6 +302,13 @@ fn mcts_solver_step<B: Board>(
     let children = match tree[curr_node].children {
         Some(children) => children,
         None => {
+            if curr_node != 0 && tree.nodes.max_len().is_some() && !tree.can_grow(curr_board.available_moves().count())
+            {
+                let result = random_playout(curr_board.clone(), rng).pov(curr_board.next_player().other());
+                tree[curr_node].increment(result);
+                return (result, false);
+            }
+
             let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
 
             curr_board.available_moves().for_each(|mv: B::Move| {
@@ -349,15 +387,55 @@ pub fn mcts_build_tree<B: Board>(
     rng: &mut impl Rng,
 ) -> Tree<B> {
     assert!(iterations > 0);
+    mcts_build_tree_until(root_board, exploration_weight, None, rng, |i| i >= iterations)
+}
 
-    let mut tree = Tree::new(root_board.clone());
+/// Same as [mcts_build_tree], but search for `time` instead of a fixed number of iterations.
+/// At least one iteration is run.
+#[cfg(feature = "native")]
+pub fn mcts_build_tree_timed<B: Board>(
+    root_board: &B,
+    time: Duration,
+    exploration_weight: f32,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    mcts_build_tree_timed_impl(root_board, time, exploration_weight, None, rng)
+}
+
+#[cfg(feature = "native")]
+fn mcts_build_tree_timed_impl<B: Board>(
+    root_board: &B,
+    time: Duration,
+    exploration_weight: f32,
+    memory_limit: Option<usize>,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    let deadline = Instant::now() + time;
+    mcts_build_tree_until(root_board, exploration_weight, memory_limit, rng, |i| {
+        i > 0 && Instant::now() >= deadline
+    })
+}
+
+/// Build a tree, running iterations until `stop` returns true for the number of iterations done so far
+/// or the root is solved. If `memory_limit` is set the tree stops growing once it would use more bytes.
+pub fn mcts_build_tree_until<B: Board>(
+    root_board: &B,
+    exploration_weight: f32,
+    memory_limit: Option<usize>,
+    rng: &mut impl Rng,
+    mut stop: impl FnMut(u64) -> bool,
+) -> Tree<B> {
+    let mut tree = match memory_limit {
+        None => Tree::new(root_board.clone()),
+        Some(memory_limit) => Tree::with_memory_limit(root_board.clone(), memory_limit),
+    };
 
     let root_outcome = root_board.outcome().map(|o| o.pov(root_board.next_player().other()));
     tree.nodes.push(Node::new(None, root_outcome));
 
-    for _ in 0..iterations {
+    for i in 0.. {
         //we've solved the root node, so we're done
-        if tree[0].solution().is_some() {
+        if stop(i) || tree[0].solution().is_some() {
             break;
         }
 
@@ -370,6 +448,7 @@ pub fn mcts_build_tree<B: Board>(
 pub struct MCTSBot<R: Rng> {
     iterations: u64,
     exploration_weight: f32,
+    memory_limit: Option<usize>,
     rng: R,
 }
 
@@ -377,8 +456,8 @@ impl<R: Rng> Debug for MCTSBot<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "MCTSBot {{ iterations: {}, exploration_weight: {} }}",
-            self.iterations, self.exploration_weight
+            "MCTSBot {{ iterations: {}, exploration_weight: {}, memory_limit: {:?} }}",
+            self.iterations, self.exploration_weight, self.memory_limit
         )
     }
 }
@@ -389,12 +468,30 @@ impl<R: Rng> MCTSBot<R> {
         MCTSBot {
             iterations,
             exploration_weight,
+            memory_limit: None,
             rng,
         }
     }
 
+    /// Stop expanding the tree once it would use more than `bytes` bytes, see [Tree::memory_bytes].
+    /// The remaining iterations still run playouts from the existing leaves.
+    pub fn with_memory_limit(self, bytes: usize) -> Self {
+        MCTSBot {
+            memory_limit: Some(bytes),
+            ..self
+        }
+    }
+
     pub fn build_tree<B: Board>(&mut self, board: &B) -> Tree<B> {
-        mcts_build_tree(board, self.iterations, self.exploration_weight, &mut self.rng)
+        self.build_tree_with_iterations(board, self.iterations)
+    }
+
+    /// Same as [build_tree](MCTSBot::build_tree) but with `iterations` instead of the iterations of this bot.
+    pub fn build_tree_with_iterations<B: Board>(&mut self, board: &B, iterations: u64) -> Tree<B> {
+        assert!(iterations > 0);
+        mcts_build_tree_until(board, self.exploration_weight, self.memory_limit, &mut self.rng, |i| {
+            i >= iterations
+        })
     }
 }
 
@@ -403,4 +500,17 @@ impl<R: Rng, B: Board> Bot<B> for MCTSBot<R> {
         assert!(!board.is_done());
         self.build_tree(board).best_move()
     }
+
+    fn select_move_with_value(&mut self, board: &B) -> (B::Move, Option<f32>) {
+        assert!(!board.is_done());
+        let tree = self.build_tree(board);
+        (tree.best_move(), Some(tree.wdl().value()))
+    }
+
+    #[cfg(feature = "native")]
+    fn select_move_timed(&mut self, board: &B, time: Duration) -> (B::Move, Option<f32>) {
+        assert!(!board.is_done());
+        let tree = mcts_build_tree_timed_impl(board, time, self.exploration_weight, self.memory_limit, &mut self.rng);
+        (tree.best_move(), Some(tree.wdl().value()))
+    }
 }
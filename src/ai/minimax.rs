@@ -37,6 +37,33 @@ pub trait Heuristic<B: Board> {
     }
 }
 
+/// A heuristic that keeps an accumulator of board features, which is updated incrementally as moves are played and
+/// undone during the search instead of being recomputed for every board.
+/// This is the idea behind the efficiently updatable neural networks (NNUE) used by chess engines.
+pub trait IncrementalHeuristic<B: Board> {
+    /// The type used to represent the heuristic value of a board.
+    type V: Copy + Eq + Ord + Neg<Output = Self::V>;
+
+    /// The features of a board.
+    type Accumulator;
+
+    /// Return a value V that such that for any possible value `v`: `-bound <= v <= bound`.
+    fn bound(&self) -> Self::V;
+
+    /// Build the accumulator of `board` from scratch.
+    fn accumulator(&self, board: &B) -> Self::Accumulator;
+
+    /// Update `acc` from the accumulator of `board` to the accumulator of `child = board.clone_and_play(mv)`.
+    fn play(&self, acc: &mut Self::Accumulator, board: &B, mv: B::Move, child: &B);
+
+    /// Revert [play](IncrementalHeuristic::play) called with the same arguments.
+    fn undo(&self, acc: &mut Self::Accumulator, board: &B, mv: B::Move, child: &B);
+
+    /// Return the heuristic value for the given board from the next player POV, `acc` is the accumulator of `board`.
+    /// See [Heuristic::value] for the meaning of `length`.
+    fn value(&self, acc: &Self::Accumulator, board: &B, length: u32) -> Self::V;
+}
+
 #[derive(Debug)]
 pub struct MinimaxResult<V, M> {
     /// The value of this board.
@@ -54,10 +81,39 @@ pub fn minimax<B: Board, H: Heuristic<B>>(
     depth: u32,
     rng: &mut impl Rng,
 ) -> MinimaxResult<H::V, B::Move> {
+    let mut state = vec![heuristic.value(board, 0)];
+    let eval = PlainEval(heuristic);
     let result = negamax_recurse(
-        heuristic,
+        &eval,
         board,
-        heuristic.value(board, 0),
+        &mut state,
+        0,
+        depth,
+        -heuristic.bound(),
+        heuristic.bound(),
+        RandomBestMoveSelector::new(rng),
+    );
+
+    if result.best_move.is_none() {
+        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
+    }
+
+    result
+}
+
+/// Same as [minimax], but with an [IncrementalHeuristic].
+pub fn minimax_incremental<B: Board, H: IncrementalHeuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    rng: &mut impl Rng,
+) -> MinimaxResult<H::V, B::Move> {
+    let mut acc = heuristic.accumulator(board);
+    let eval = IncrementalEval(heuristic);
+    let result = negamax_recurse(
+        &eval,
+        board,
+        &mut acc,
         0,
         depth,
         -heuristic.bound(),
@@ -75,10 +131,11 @@ pub fn minimax<B: Board, H: Heuristic<B>>(
 /// Evaluate the board using minimax with the given heuristic up to the given depth.
 /// Only returns the value without selecting a move, and so doesn't require an `Rng`.
 pub fn minimax_value<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth: u32) -> H::V {
+    let mut state = vec![heuristic.value(board, 0)];
     negamax_recurse(
-        heuristic,
+        &PlainEval(heuristic),
         board,
-        heuristic.value(board, 0),
+        &mut state,
         0,
         depth,
         -heuristic.bound(),
@@ -88,6 +145,66 @@ pub fn minimax_value<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth:
     .value
 }
 
+/// The evaluation used during the search, which abstracts over [Heuristic] and [IncrementalHeuristic].
+/// `State` is updated in place as the search moves up and down the tree.
+trait SearchEval<B: Board> {
+    type V: Copy + Eq + Ord + Neg<Output = Self::V>;
+    type State;
+
+    fn bound(&self) -> Self::V;
+    fn value(&self, state: &Self::State, board: &B, length: u32) -> Self::V;
+    fn play(&self, state: &mut Self::State, board: &B, length: u32, mv: B::Move, child: &B);
+    fn undo(&self, state: &mut Self::State, board: &B, length: u32, mv: B::Move, child: &B);
+}
+
+/// The state is the stack of values along the current path.
+struct PlainEval<'a, H>(&'a H);
+
+impl<B: Board, H: Heuristic<B>> SearchEval<B> for PlainEval<'_, H> {
+    type V = H::V;
+    type State = Vec<H::V>;
+
+    fn bound(&self) -> Self::V {
+        self.0.bound()
+    }
+
+    fn value(&self, state: &Self::State, _: &B, _: u32) -> Self::V {
+        *state.last().unwrap()
+    }
+
+    fn play(&self, state: &mut Self::State, board: &B, length: u32, mv: B::Move, child: &B) {
+        let board_value = *state.last().unwrap();
+        state.push(self.0.value_update(board, board_value, length, mv, child));
+    }
+
+    fn undo(&self, state: &mut Self::State, _: &B, _: u32, _: B::Move, _: &B) {
+        state.pop();
+    }
+}
+
+struct IncrementalEval<'a, H>(&'a H);
+
+impl<B: Board, H: IncrementalHeuristic<B>> SearchEval<B> for IncrementalEval<'_, H> {
+    type V = H::V;
+    type State = H::Accumulator;
+
+    fn bound(&self) -> Self::V {
+        self.0.bound()
+    }
+
+    fn value(&self, state: &Self::State, board: &B, length: u32) -> Self::V {
+        self.0.value(state, board, length)
+    }
+
+    fn play(&self, state: &mut Self::State, board: &B, _: u32, mv: B::Move, child: &B) {
+        self.0.play(state, board, mv, child)
+    }
+
+    fn undo(&self, state: &mut Self::State, board: &B, _: u32, mv: B::Move, child: &B) {
+        self.0.undo(state, board, mv, child)
+    }
+}
+
 /// This is a trait so negamax_recurse is instantiated twice,
 /// once for the top-level search with move selection and once for deeper nodes without any moves.
 trait MoveSelector {
@@ -126,35 +243,35 @@ impl<R: Rng> MoveSelector for RandomBestMoveSelector<'_, R> {
 /// The core minimax implementation.
 /// Fail-Soft Alpha-Beta Negamax, implementation based on
 /// <https://www.chessprogramming.org/Alpha-Beta#Outside_the_Bounds>
-fn negamax_recurse<B: Board, H: Heuristic<B>>(
-    heuristic: &H,
+fn negamax_recurse<B: Board, E: SearchEval<B>>(
+    eval: &E,
     board: &B,
-    board_heuristic: H::V,
+    state: &mut E::State,
     length: u32,
     depth_left: u32,
-    alpha: H::V,
-    beta: H::V,
+    alpha: E::V,
+    beta: E::V,
     mut move_selector: impl MoveSelector,
-) -> MinimaxResult<H::V, B::Move> {
+) -> MinimaxResult<E::V, B::Move> {
     if depth_left == 0 || board.is_done() {
         return MinimaxResult {
-            value: board_heuristic,
+            value: eval.value(state, board, length),
             best_move: None,
         };
     }
 
-    let mut best_value = -heuristic.bound();
+    let mut best_value = -eval.bound();
     let mut best_move: Option<B::Move> = None;
     let mut alpha = alpha;
 
     let early = board.available_moves().find_map(|mv: B::Move| {
         let child = board.clone_and_play(mv);
-        let child_heuristic = heuristic.value_update(board, board_heuristic, length, mv, &child);
 
+        eval.play(state, board, length, mv, &child);
         let child_value = -negamax_recurse(
-            heuristic,
+            eval,
             &child,
-            child_heuristic,
+            state,
             length + 1,
             depth_left - 1,
             -beta,
@@ -162,6 +279,7 @@ fn negamax_recurse<B: Board, H: Heuristic<B>>(
             NoMoveSelector,
         )
         .value;
+        eval.undo(state, board, length, mv, &child);
 
         if child_value >= beta {
             //early return, this stops looping over the available moves
@@ -238,3 +356,43 @@ impl<B: Board, H: Heuristic<B> + Debug, R: Rng> Bot<B> for MiniMaxBot<B, H, R> {
             .unwrap()
     }
 }
+
+/// Same as [MiniMaxBot], but with an [IncrementalHeuristic].
+pub struct IncrementalMiniMaxBot<B: Board, H: IncrementalHeuristic<B>, R: Rng> {
+    depth: u32,
+    heuristic: H,
+    rng: R,
+    ph: PhantomData<B>,
+}
+
+impl<B: Board, H: IncrementalHeuristic<B> + Debug, R: Rng> Debug for IncrementalMiniMaxBot<B, H, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IncrementalMiniMaxBot {{ depth: {}, heuristic: {:?} }}",
+            self.depth, self.heuristic
+        )
+    }
+}
+
+impl<B: Board, H: IncrementalHeuristic<B>, R: Rng> IncrementalMiniMaxBot<B, H, R> {
+    pub fn new(depth: u32, heuristic: H, rng: R) -> Self {
+        assert!(depth > 0, "requires depth>0 to find the best move");
+        IncrementalMiniMaxBot {
+            depth,
+            heuristic,
+            rng,
+            ph: PhantomData,
+        }
+    }
+}
+
+impl<B: Board, H: IncrementalHeuristic<B> + Debug, R: Rng> Bot<B> for IncrementalMiniMaxBot<B, H, R> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        assert!(!board.is_done());
+        // SAFETY: unwrap is safe for the same reasons as in MiniMaxBot
+        minimax_incremental(board, &self.heuristic, self.depth, &mut self.rng)
+            .best_move
+            .unwrap()
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt::Debug;
+use std::time::Duration;
 
 use crate::board::Board;
 
+pub mod external;
 pub mod mcts;
 pub mod minimax;
 pub mod simple;
@@ -13,6 +15,21 @@ pub trait Bot<B: Board>: Debug {
     /// `self` is mutable to allow for random state, this method is not supposed to
     /// modify `self` in any other significant way.
     fn select_move(&mut self, board: &B) -> B::Move;
+
+    /// Pick a move like [select_move](Bot::select_move), and also return the expected value of `board`
+    /// from the POV of `board.next_player()` in the range `-1..=1`, if this bot can estimate it.
+    fn select_move_with_value(&mut self, board: &B) -> (B::Move, Option<f32>) {
+        (self.select_move(board), None)
+    }
+
+    /// Pick a move like [select_move_with_value](Bot::select_move_with_value) within about `time`.
+    ///
+    /// Anytime bots like [MCTSBot](crate::ai::mcts::MCTSBot) search for as long as they're allowed to,
+    /// the default implementation ignores `time` and is only appropriate for bots that are fast anyway.
+    fn select_move_timed(&mut self, board: &B, time: Duration) -> (B::Move, Option<f32>) {
+        let _ = time;
+        self.select_move_with_value(board)
+    }
 }
 
 impl<B: Board, F: FnMut(&B) -> B::Move + Debug> Bot<B> for F {
@@ -1,8 +1,12 @@
 //! Two simple bots: `RandomBot` and `RolloutBot`.
+use std::cmp::min;
 use std::fmt::{Debug, Formatter};
 
 use internal_iterator::InternalIterator;
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "native")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::ai::Bot;
 use crate::board::Board;
@@ -35,11 +39,17 @@ impl<B: Board, R: Rng> Bot<B> for RandomBot<R> {
 ///
 /// The same number of simulations `rollouts / nb_moves` is done for
 /// each move, and the move resulting in the best average score is selected.
+/// With the `native` feature the simulations are spread over the rayon thread pool.
+/// Each batch of simulations gets its own rng, seeded from the rng of the bot,
+/// so the selected move doesn't depend on the number of threads.
 pub struct RolloutBot<R: Rng> {
     rollouts: u32,
     rng: R,
 }
 
+/// The number of rollouts in a single batch, which is the unit of work that is run in parallel.
+const ROLLOUTS_PER_BATCH: u32 = 16;
+
 impl<R: Rng> Debug for RolloutBot<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "RolloutBot {{ rollouts: {} }}", self.rollouts)
@@ -54,25 +64,50 @@ impl<R: Rng> RolloutBot<R> {
 
 impl<B: Board, R: Rng> Bot<B> for RolloutBot<R> {
     fn select_move(&mut self, board: &B) -> B::Move {
-        let rollouts_per_move = self.rollouts / board.available_moves().count() as u32;
-
-        board
-            .available_moves()
-            .max_by_key(|&mv| {
-                let child = board.clone_and_play(mv);
-
-                let score: i64 = (0..rollouts_per_move)
-                    .map(|_| {
-                        let mut copy = child.clone();
-                        while !copy.is_done() {
-                            copy.play(copy.random_available_move(&mut self.rng))
-                        }
-                        copy.outcome().unwrap().pov(board.next_player()).sign::<i64>()
-                    })
-                    .sum();
-
-                score
-            })
-            .unwrap()
+        let moves: Vec<B::Move> = board.available_moves().collect();
+        let rollouts_per_move = self.rollouts / moves.len() as u32;
+
+        // (move index, rollout count, seed) for each batch
+        let mut batches = vec![];
+        for mv_i in 0..moves.len() {
+            let mut left = rollouts_per_move;
+            while left > 0 {
+                let count = min(left, ROLLOUTS_PER_BATCH);
+                batches.push((mv_i, count, self.rng.gen::<u64>()));
+                left -= count;
+            }
+        }
+
+        let run_batch =
+            |&(mv_i, count, seed): &(usize, u32, u64)| (mv_i, rollout_score(board, moves[mv_i], count, seed));
+
+        #[cfg(feature = "native")]
+        let batch_scores: Vec<(usize, i64)> = batches.par_iter().map(run_batch).collect();
+        #[cfg(not(feature = "native"))]
+        let batch_scores: Vec<(usize, i64)> = batches.iter().map(run_batch).collect();
+
+        let mut scores = vec![0; moves.len()];
+        for (mv_i, score) in batch_scores {
+            scores[mv_i] += score;
+        }
+
+        let best_i = (0..moves.len()).max_by_key(|&i| scores[i]).unwrap();
+        moves[best_i]
     }
 }
+
+/// The total score for the player to move on `board` of `count` random games after playing `mv`.
+fn rollout_score<B: Board>(board: &B, mv: B::Move, count: u32, seed: u64) -> i64 {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let child = board.clone_and_play(mv);
+
+    (0..count)
+        .map(|_| {
+            let mut copy = child.clone();
+            while !copy.is_done() {
+                copy.play(copy.random_available_move(&mut rng))
+            }
+            copy.outcome().unwrap().pov(board.next_player()).sign::<i64>()
+        })
+        .sum()
+}
@@ -0,0 +1,166 @@
+//! Measure the speed of the board implementations and the bots, to detect performance regressions.
+//!
+//! Usage: `bench --game <ataxx|chess|sttt> [options]`
+//!
+//! Reports the perft speed, the random playout speed and the number of MCTS iterations per second,
+//! all starting from the start position. This crate does not contain neural networks,
+//! so there is no network evaluation benchmark.
+//!
+//! Options:
+//! * `--perft-depth <n>`: the perft depth, default 4
+//! * `--playouts <n>`: the number of random playouts, default 1000
+//! * `--mcts <iterations>[:<exploration_weight>]`: the MCTS search that is timed, default `10000:2`
+//! * `--repeat <n>`: run each benchmark this many times and report the fastest run, default 3
+use std::str::FromStr;
+use std::time::Instant;
+
+use rand::thread_rng;
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::games::sttt::STTTBoard;
+use board_game::util::game_stats::perft;
+
+fn main() {
+    if let Err(e) = main_impl() {
+        eprintln!("Error: {}", e);
+        eprintln!("Run with --help for usage information");
+        std::process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+struct Args {
+    game: String,
+    perft_depth: u32,
+    playouts: u32,
+    mcts_iterations: u64,
+    exploration_weight: f32,
+    repeat: u32,
+}
+
+fn main_impl() -> Result<(), String> {
+    let args = parse_args()?;
+
+    match &*args.game {
+        "ataxx" => run_benches(&args, AtaxxBoard::default()),
+        "chess" => run_benches(&args, ChessBoard::default()),
+        "sttt" => run_benches(&args, STTTBoard::default()),
+        _ => return Err(format!("Unknown game '{}'", args.game)),
+    }
+
+    Ok(())
+}
+
+fn run_benches<B: Board>(args: &Args, start: B) {
+    println!("game: {}", args.game);
+
+    let (time, nodes) = fastest(args.repeat, || perft(&start, args.perft_depth));
+    println!(
+        "perft depth {}: {} nodes in {:.3}s, {:.0} nodes/s",
+        args.perft_depth,
+        nodes,
+        time,
+        nodes as f32 / time
+    );
+
+    let mut rng = thread_rng();
+    let (time, moves) = fastest(args.repeat, || {
+        let mut moves = 0;
+        for _ in 0..args.playouts {
+            let mut board = start.clone();
+            while !board.is_done() {
+                board.play(board.random_available_move(&mut rng));
+                moves += 1;
+            }
+        }
+        moves
+    });
+    println!(
+        "random playouts: {} in {:.3}s, {:.0} playouts/s, {:.0} moves/s, {:.1} moves per playout",
+        args.playouts,
+        time,
+        args.playouts as f32 / time,
+        moves as f32 / time,
+        moves as f32 / args.playouts as f32
+    );
+
+    let mut bot = MCTSBot::new(args.mcts_iterations, args.exploration_weight, thread_rng());
+    let (time, nodes) = fastest(args.repeat, || bot.build_tree(&start).nodes.len());
+    println!(
+        "mcts: {} iterations in {:.3}s, {:.0} iterations/s, {} nodes",
+        args.mcts_iterations,
+        time,
+        args.mcts_iterations as f32 / time,
+        nodes
+    );
+}
+
+/// Run `f` `repeat` times, returns the shortest time in seconds and the result of the last run.
+fn fastest<T>(repeat: u32, mut f: impl FnMut() -> T) -> (f32, T) {
+    let mut best_time = f32::INFINITY;
+    let mut result = None;
+
+    for _ in 0..repeat {
+        let start = Instant::now();
+        result = Some(f());
+        best_time = best_time.min((Instant::now() - start).as_secs_f32());
+    }
+
+    (best_time, result.unwrap())
+}
+
+const USAGE: &str = "Usage: bench --game <ataxx|chess|sttt> [options]
+Options: --perft-depth <n>, --playouts <n>, --mcts <iterations>[:<exploration_weight>], --repeat <n>";
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        game: String::new(),
+        perft_depth: 4,
+        playouts: 1000,
+        mcts_iterations: 10_000,
+        exploration_weight: 2.0,
+        repeat: 3,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("Missing value for {}", arg));
+
+        match &*arg {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "--game" => args.game = value()?,
+            "--perft-depth" => args.perft_depth = parse(&value()?)?,
+            "--playouts" => args.playouts = parse(&value()?)?,
+            "--mcts" => {
+                let value = value()?;
+                match value.split_once(':') {
+                    Some((iterations, weight)) => {
+                        args.mcts_iterations = parse(iterations)?;
+                        args.exploration_weight = parse(weight)?;
+                    }
+                    None => args.mcts_iterations = parse(&value)?,
+                }
+            }
+            "--repeat" => args.repeat = parse(&value()?)?,
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    if args.game.is_empty() {
+        return Err("Missing --game".to_string());
+    }
+    if args.playouts == 0 || args.mcts_iterations == 0 || args.repeat == 0 {
+        return Err("--playouts, --mcts and --repeat must be positive".to_string());
+    }
+    Ok(args)
+}
+
+fn parse<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid value '{}'", s))
+}
@@ -0,0 +1,597 @@
+//! Run a match between two bots, or a round-robin tournament or gauntlet between more bots, and report the results.
+//!
+//! Usage: `match --game <ataxx|chess> --bot <spec> --bot <spec> [options]`
+//!
+//! Bot specs:
+//! * `random`
+//! * `rollout:<rollouts>`
+//! * `mcts:<iterations>[:<exploration_weight>]`
+//! * `minimax:<depth>`, using the default heuristic of the game
+//! * `uai:<command>` or `uci:<command>`, an external engine started with the given command line
+//!
+//! Options:
+//! * `--games <n>`: the number of games per pairing, default 10
+//! * `--sprt <elo0>:<elo1>[:<alpha>:<beta>]`: stop each pairing as soon as a sequential probability ratio test
+//!   concludes, `--games` is then the maximum number of games, `alpha` and `beta` default to 0.05
+//! * `--both-sides`: play each start position twice with the bots switching sides
+//! * `--gauntlet`: only play the first bot against each of the other bots, and rate it against them
+//! * `--openings <file>`: a file with one FEN per line, used as start positions in turn
+//! * `--movetime <ms>`: the time per move for external engines, default 100
+//! * `--max-moves <n>`: adjudicate games as a draw after this many moves
+//! * `--repetitions <n>`: adjudicate games as a draw when a position occurs this many times
+//! * `--adjudicate-win <threshold>:<moves>`: adjudicate a win when the bots report a value of at least `threshold`
+//!   for the same player during `moves` consecutive moves
+//! * `--tc <seconds>[+<increment>]`: a clock per game for every bot, bots that run out of time lose the game.
+//!   External engines are asked to move within their budget instead of `--movetime`
+//! * `--timeout <ms>`: the extra time external engines get before being considered unresponsive, default 1000
+//! * `--concurrency <n>`: the number of games played at the same time, defaults to the number of cores
+//! * `--pgn <file>`: write all games to this file
+//! * `--replay <file>`: write all games to this file in the [replay](board_game::util::replay) format, including the time
+//!   and evaluation of each move
+//! * `--status <file>`: keep the results of the current pairing so far in this JSON file, updated after every game
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rand::thread_rng;
+
+use board_game::ai::external::{ExternalBoard, ExternalBot, Protocol};
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::minimax::MiniMaxBot;
+use board_game::ai::simple::{RandomBot, RolloutBot};
+use board_game::ai::Bot;
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::{move_to_san, ChessBoard};
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::util::bot_game::{
+    parse_openings, run_with, Adjudication, BotGameResult, GameEvent, MatchOptions, TimeControl,
+};
+use board_game::util::elo::{fit_elo, fit_elo_anchored, Hypothesis, Sprt};
+use board_game::util::pgn::PgnGame;
+use board_game::util::replay::ReplayRecorder;
+
+fn main() {
+    if let Err(e) = main_impl() {
+        eprintln!("Error: {}", e);
+        eprintln!("Run with --help for usage information");
+        std::process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+struct Args {
+    game: String,
+    bots: Vec<BotSpec>,
+    games: u32,
+    sprt: Option<Sprt>,
+    both_sides: bool,
+    gauntlet: bool,
+    openings: Option<String>,
+    move_time: Duration,
+    time_control: Option<TimeControl>,
+    max_moves: Option<u32>,
+    repetitions: Option<u32>,
+    adjudicate_win: Option<(f32, u32)>,
+    timeout: Duration,
+    concurrency: Option<usize>,
+    pgn: Option<String>,
+    replay: Option<String>,
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum BotSpec {
+    Random,
+    Rollout(u32),
+    Mcts(u64, f32),
+    Minimax(u32),
+    External(Protocol, String),
+}
+
+/// The start position, the moves and whether the left bot played the first move.
+type GameRecord<B> = (B, Vec<<B as Board>::Move>, bool);
+
+/// The game-specific parts of running a match.
+struct Game<B: ExternalBoard> {
+    start: B,
+    minimax: fn(u32) -> Box<dyn Bot<B>>,
+    /// The move notation used in the PGN file.
+    notation: fn(&B, B::Move) -> String,
+    repetition_key: fn(&B) -> u64,
+}
+
+fn main_impl() -> Result<(), String> {
+    let args = parse_args()?;
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(concurrency) = args.concurrency {
+        pool = pool.num_threads(concurrency);
+    }
+    let pool = pool.build().map_err(|e| e.to_string())?;
+
+    pool.install(|| match &*args.game {
+        "ataxx" => run_games(
+            &args,
+            Game {
+                start: AtaxxBoard::default(),
+                minimax: |depth| Box::new(MiniMaxBot::new(depth, AtaxxTileHeuristic::default(), thread_rng())),
+                notation: |_, mv| mv.to_string(),
+                repetition_key: AtaxxBoard::zobrist,
+            },
+        ),
+        "chess" => run_games(
+            &args,
+            Game {
+                start: ChessBoard::default(),
+                minimax: |depth| Box::new(MiniMaxBot::new(depth, ChessPieceValueHeuristic, thread_rng())),
+                notation: |board, mv| move_to_san(board, mv),
+                repetition_key: ChessBoard::repetition_key,
+            },
+        ),
+        _ => Err(format!("Unknown game '{}'", args.game)),
+    })
+}
+
+fn run_games<B: ExternalBoard>(args: &Args, game: Game<B>) -> Result<(), String> {
+    let openings = match &args.openings {
+        None => vec![game.start.clone()],
+        Some(path) => {
+            let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read openings: {}", e))?;
+            parse_openings(&content)?
+        }
+    };
+    if openings.is_empty() {
+        return Err("No openings found".to_string());
+    }
+
+    let pgn = match &args.pgn {
+        None => None,
+        Some(path) => Some(Mutex::new(
+            File::create(path).map_err(|e| format!("Failed to create pgn file: {}", e))?,
+        )),
+    };
+
+    let replay = match &args.replay {
+        None => None,
+        Some(path) => Some(ReplayRecorder::new(
+            File::create(path).map_err(|e| format!("Failed to create replay file: {}", e))?,
+        )),
+    };
+
+    // start each bot once, so an engine that can't be started is reported before any game is played
+    for spec in &args.bots {
+        spec.try_build(args, &game)?;
+    }
+
+    let bot_count = args.bots.len();
+    // scores[i][j] is the score of bot i against bot j
+    let mut scores = vec![vec![None; bot_count]; bot_count];
+    let mut points = vec![vec![0.0; bot_count]; bot_count];
+    let mut game_counts = vec![vec![0; bot_count]; bot_count];
+    let next_opening = AtomicUsize::new(0);
+
+    let pairs = (0..bot_count)
+        .flat_map(|i| ((i + 1)..bot_count).map(move |j| (i, j)))
+        .filter(|&(i, _)| !args.gauntlet || i == 0);
+    for (i, j) in pairs {
+        let (spec_l, spec_r) = (&args.bots[i], &args.bots[j]);
+        let (name_l, name_r) = (spec_l.to_string(), spec_r.to_string());
+        let games: Mutex<Vec<Option<GameRecord<B>>>> = Mutex::new(vec![]);
+
+        let record_game = |event: &GameEvent<B>| match *event {
+            GameEvent::Start { game_id, board } => {
+                let mut games = games.lock().unwrap();
+                if games.len() <= game_id as usize {
+                    games.resize_with(game_id as usize + 1, || None);
+                }
+                games[game_id as usize] = Some((board.clone(), vec![], true));
+            }
+            GameEvent::Move { game_id, mv, left, .. } => {
+                let mut games = games.lock().unwrap();
+                let (_, moves, left_first) = games[game_id as usize].as_mut().unwrap();
+                if moves.is_empty() {
+                    *left_first = left;
+                }
+                moves.push(mv);
+            }
+            GameEvent::End { game_id, outcome, .. } => {
+                let (start, moves, left_first) = games.lock().unwrap()[game_id as usize].take().unwrap();
+                let left_is_a = left_first == (start.next_player() == Player::A);
+                let (name_a, name_b) = if left_is_a {
+                    (&name_l, &name_r)
+                } else {
+                    (&name_r, &name_l)
+                };
+                println!("{} vs {}: {}", name_a, name_b, outcome_str(outcome));
+
+                if let Some(pgn) = &pgn {
+                    let mut tags = vec![
+                        ("Event".to_string(), format!("{} vs {}", name_l, name_r)),
+                        ("Round".to_string(), (game_id + 1).to_string()),
+                        ("White".to_string(), name_a.clone()),
+                        ("Black".to_string(), name_b.clone()),
+                    ];
+                    if start != game.start {
+                        tags.push(("SetUp".to_string(), "1".to_string()));
+                        tags.push(("FEN".to_string(), start.external_fen()));
+                    }
+                    let record = PgnGame::from_game(&start, &moves, tags, game.notation);
+                    // a failing pgn file should not abort the match
+                    if let Err(e) = writeln!(pgn.lock().unwrap(), "{}", record) {
+                        eprintln!("Failed to write pgn: {}", e);
+                    }
+                }
+            }
+        };
+
+        let observer = |event: &GameEvent<B>| {
+            if let Some(replay) = &replay {
+                replay.observe(event);
+            }
+            record_game(event)
+        };
+
+        let start = || openings[next_opening.fetch_add(1, Ordering::Relaxed) % openings.len()].clone();
+        let build_l = || spec_l.build(args, &game);
+        let build_r = || spec_r.build(args, &game);
+        let options = MatchOptions {
+            sprt: args.sprt,
+            time_control: args.time_control,
+            adjudication: Some(Adjudication {
+                max_moves: args.max_moves,
+                repetitions: args.repetitions.map(|count| (count, game.repetition_key)),
+                win: args.adjudicate_win,
+            }),
+            // a misbehaving engine should not abort the other games
+            catch_panics: true,
+            status_file: args.status.as_ref().map(PathBuf::from),
+            results: None,
+        };
+        let result = run_with(start, build_l, build_r, args.games, args.both_sides, &options, observer);
+
+        print_result(&name_l, &name_r, &result);
+        let score_l = result.win_rate_l + 0.5 * result.draw_rate;
+        scores[i][j] = Some(score_l);
+        scores[j][i] = Some(1.0 - score_l);
+        points[i][j] = result.wins_l as f32 + 0.5 * result.draws as f32;
+        points[j][i] = result.wins_r as f32 + 0.5 * result.draws as f32;
+        game_counts[i][j] = result.game_count;
+        game_counts[j][i] = result.game_count;
+    }
+
+    if args.gauntlet {
+        // the opponents have no known ratings, so the candidate is rated against their average
+        let anchors = vec![0.0; bot_count - 1];
+        let elo = fit_elo_anchored(&anchors, &points[0][1..], &game_counts[0][1..]);
+        println!("{} gauntlet elo: {:+.0}", args.bots[0], elo);
+    } else if bot_count > 2 {
+        print_crosstable(&args.bots, &scores, &fit_elo(&points, &game_counts));
+    }
+
+    if let Some(replay) = replay {
+        replay.finish().map_err(|e| format!("Failed to write replay: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn print_result(name_l: &str, name_r: &str, result: &BotGameResult) {
+    println!();
+    println!("{} vs {}", name_l, name_r);
+    println!(
+        "  games: {}, wins: {:.1}%, draws: {:.1}%, losses: {:.1}%, elo: {:+.1} +/- {:.1}, los: {:.1}%",
+        result.game_count,
+        result.win_rate_l * 100.0,
+        result.draw_rate * 100.0,
+        result.win_rate_r * 100.0,
+        result.elo_l,
+        result.elo_error_l,
+        result.los_l * 100.0
+    );
+    let significance = result.trinomial_significance();
+    let p_value = match result.pentanomial_significance() {
+        None => {
+            println!("  p-value: {:.3}", significance.p_value);
+            significance.p_value
+        }
+        Some(pairs) => {
+            println!(
+                "  p-value: {:.3}, paired p-value: {:.3}, paired los: {:.1}%",
+                significance.p_value,
+                pairs.p_value,
+                pairs.los * 100.0
+            );
+            pairs.p_value
+        }
+    };
+    if p_value > 0.05 {
+        println!("  the difference is not significant at the 5% level, more games are needed");
+    }
+    println!(
+        "  average game length: {:.1}, time per move: {:.4}s vs {:.4}s",
+        result.game_length, result.time_l, result.time_r
+    );
+    let (times_l, times_r) = (result.move_times_l, result.move_times_r);
+    println!(
+        "  time per move p50/p95/max: {:.4}s/{:.4}s/{:.4}s vs {:.4}s/{:.4}s/{:.4}s",
+        times_l.p50, times_l.p95, times_l.max, times_r.p50, times_r.p95, times_r.max
+    );
+    if result.adjudicated > 0 {
+        println!("  adjudicated: {}", result.adjudicated);
+    }
+    if result.time_forfeits_l + result.time_forfeits_r > 0 {
+        println!(
+            "  lost on time: {} vs {}",
+            result.time_forfeits_l, result.time_forfeits_r
+        );
+    }
+    for panicked in &result.panics {
+        println!(
+            "  game {} aborted after {} moves: {}",
+            panicked.game_id + 1,
+            panicked.moves.len(),
+            panicked.message
+        );
+    }
+    if let Some(sprt) = &result.sprt {
+        let (lower, upper) = sprt.sprt.bounds();
+        let conclusion = match sprt.accepted {
+            None => "inconclusive",
+            Some(Hypothesis::H0) => "H0 accepted",
+            Some(Hypothesis::H1) => "H1 accepted",
+        };
+        println!(
+            "  sprt elo0: {}, elo1: {}: {}, llr: {:.3} in [{:.3}, {:.3}]",
+            sprt.sprt.elo0,
+            sprt.sprt.elo1,
+            conclusion,
+            sprt.llr.last().copied().unwrap_or(0.0),
+            lower,
+            upper
+        );
+    }
+    println!();
+}
+
+fn print_crosstable(bots: &[BotSpec], scores: &[Vec<Option<f32>>], elo: &[f32]) {
+    let names: Vec<String> = bots.iter().map(|b| b.to_string()).collect();
+    let width = names.iter().map(|n| n.len()).max().unwrap().max(6);
+
+    print!("{:width$}", "", width = width);
+    for name in &names {
+        print!("  {:>width$}", name, width = width);
+    }
+    println!("  {:>width$}  {:>6}", "total", "elo", width = width);
+
+    for (i, name) in names.iter().enumerate() {
+        print!("{:width$}", name, width = width);
+        for score in &scores[i] {
+            match score {
+                Some(score) => print!("  {:>width$.1}", score * 100.0, width = width),
+                None => print!("  {:>width$}", "-", width = width),
+            }
+        }
+        let total = scores[i].iter().flatten().sum::<f32>() / (names.len() - 1) as f32;
+        println!("  {:>width$.1}  {:>+6.0}", total * 100.0, elo[i], width = width);
+    }
+}
+
+fn outcome_str(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::WonBy(Player::A) => "1-0",
+        Outcome::WonBy(Player::B) => "0-1",
+        Outcome::Draw => "1/2-1/2",
+    }
+}
+
+impl BotSpec {
+    fn build<B: ExternalBoard>(&self, args: &Args, game: &Game<B>) -> DynBot<B> {
+        // the external engines were already started once by run_games, so this only fails if they broke since
+        self.try_build(args, game).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_build<B: ExternalBoard>(&self, args: &Args, game: &Game<B>) -> Result<DynBot<B>, String> {
+        let bot: Box<dyn Bot<B>> = match *self {
+            BotSpec::Random => Box::new(RandomBot::new(thread_rng())),
+            BotSpec::Rollout(rollouts) => Box::new(RolloutBot::new(rollouts, thread_rng())),
+            BotSpec::Mcts(iterations, exploration_weight) => {
+                Box::new(MCTSBot::new(iterations, exploration_weight, thread_rng()))
+            }
+            BotSpec::Minimax(depth) => (game.minimax)(depth),
+            BotSpec::External(protocol, ref command_line) => {
+                let mut parts = command_line.split_whitespace();
+                let mut command = Command::new(parts.next().unwrap());
+                command.args(parts);
+
+                let bot = ExternalBot::new(command, protocol, args.move_time, args.timeout)
+                    .map_err(|e| format!("Failed to start '{}': {}", command_line, e))?;
+                Box::new(bot)
+            }
+        };
+        Ok(DynBot(bot))
+    }
+}
+
+impl FromStr for BotSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, params) = match s.split_once(':') {
+            Some((kind, params)) => (kind, params),
+            None => (s, ""),
+        };
+        let params: Vec<&str> = if params.is_empty() {
+            vec![]
+        } else {
+            params.split(':').collect()
+        };
+
+        let spec = match (kind, &params[..]) {
+            ("random", []) => BotSpec::Random,
+            ("rollout", [rollouts]) => BotSpec::Rollout(parse(rollouts)?),
+            ("mcts", [iterations]) => BotSpec::Mcts(parse(iterations)?, 2.0),
+            ("mcts", [iterations, weight]) => BotSpec::Mcts(parse(iterations)?, parse(weight)?),
+            ("minimax", [depth]) => BotSpec::Minimax(parse(depth)?),
+            ("uai", _) | ("uci", _) => {
+                let command = s[kind.len()..].trim_start_matches(':').trim();
+                if command.is_empty() {
+                    return Err(format!("Missing engine command in '{}'", s));
+                }
+                let protocol = if kind == "uai" { Protocol::Uai } else { Protocol::Uci };
+                BotSpec::External(protocol, command.to_string())
+            }
+            _ => return Err(format!("Invalid bot spec '{}'", s)),
+        };
+
+        match spec {
+            BotSpec::Mcts(0, _) | BotSpec::Minimax(0) => Err(format!("Bot parameter must be positive in '{}'", s)),
+            spec => Ok(spec),
+        }
+    }
+}
+
+impl Display for BotSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotSpec::Random => write!(f, "random"),
+            BotSpec::Rollout(rollouts) => write!(f, "rollout:{}", rollouts),
+            BotSpec::Mcts(iterations, weight) => write!(f, "mcts:{}:{}", iterations, weight),
+            BotSpec::Minimax(depth) => write!(f, "minimax:{}", depth),
+            BotSpec::External(protocol, command) => {
+                let kind = if *protocol == Protocol::Uai { "uai" } else { "uci" };
+                write!(f, "{}:{}", kind, command)
+            }
+        }
+    }
+}
+
+/// Wrapper to use bots of different types in the same match.
+#[derive(Debug)]
+struct DynBot<B: Board>(Box<dyn Bot<B>>);
+
+impl<B: Board> Bot<B> for DynBot<B> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        self.0.select_move(board)
+    }
+
+    fn select_move_with_value(&mut self, board: &B) -> (B::Move, Option<f32>) {
+        self.0.select_move_with_value(board)
+    }
+
+    fn select_move_timed(&mut self, board: &B, time: Duration) -> (B::Move, Option<f32>) {
+        self.0.select_move_timed(board, time)
+    }
+}
+
+const USAGE: &str = "Usage: match --game <ataxx|chess> --bot <spec> --bot <spec> [options]
+Bot specs: random, rollout:<rollouts>, mcts:<iterations>[:<exploration_weight>], minimax:<depth>,
+           uai:<command>, uci:<command>
+Options: --games <n>, --sprt <elo0>:<elo1>[:<alpha>:<beta>], --both-sides, --gauntlet, --openings <file>,
+         --max-moves <n>, --repetitions <n>, --adjudicate-win <threshold>:<moves>,
+         --movetime <ms>, --tc <seconds>[+<increment>], --timeout <ms>, --concurrency <n>,
+         --pgn <file>, --replay <file>, --status <file>";
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        game: String::new(),
+        bots: vec![],
+        games: 10,
+        sprt: None,
+        both_sides: false,
+        gauntlet: false,
+        openings: None,
+        move_time: Duration::from_millis(100),
+        time_control: None,
+        max_moves: None,
+        repetitions: None,
+        adjudicate_win: None,
+        timeout: Duration::from_millis(1000),
+        concurrency: None,
+        pgn: None,
+        replay: None,
+        status: None,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("Missing value for {}", arg));
+
+        match &*arg {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "--game" => args.game = value()?,
+            "--bot" => args.bots.push(value()?.parse()?),
+            "--games" => args.games = parse(&value()?)?,
+            "--sprt" => args.sprt = Some(parse_sprt(&value()?)?),
+            "--both-sides" => args.both_sides = true,
+            "--gauntlet" => args.gauntlet = true,
+            "--openings" => args.openings = Some(value()?),
+            "--movetime" => args.move_time = Duration::from_millis(parse(&value()?)?),
+            "--max-moves" => args.max_moves = Some(parse(&value()?)?),
+            "--repetitions" => args.repetitions = Some(parse(&value()?)?),
+            "--adjudicate-win" => {
+                let value = value()?;
+                let (threshold, moves) = value
+                    .split_once(':')
+                    .ok_or_else(|| format!("Invalid adjudication '{}'", value))?;
+                args.adjudicate_win = Some((parse(threshold)?, parse(moves)?));
+            }
+            "--tc" => args.time_control = Some(parse_time_control(&value()?)?),
+            "--timeout" => args.timeout = Duration::from_millis(parse(&value()?)?),
+            "--concurrency" => args.concurrency = Some(parse(&value()?)?),
+            "--pgn" => args.pgn = Some(value()?),
+            "--replay" => args.replay = Some(value()?),
+            "--status" => args.status = Some(value()?),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    if args.game.is_empty() {
+        return Err("Missing --game".to_string());
+    }
+    if args.bots.len() < 2 {
+        return Err("At least two bots are required".to_string());
+    }
+    Ok(args)
+}
+
+fn parse_sprt(s: &str) -> Result<Sprt, String> {
+    let params: Vec<f32> = s.split(':').map(parse).collect::<Result<_, _>>()?;
+    let (elo0, elo1, alpha, beta) = match params[..] {
+        [elo0, elo1] => (elo0, elo1, 0.05, 0.05),
+        [elo0, elo1, alpha, beta] => (elo0, elo1, alpha, beta),
+        _ => return Err(format!("Invalid sprt '{}'", s)),
+    };
+    if !(elo0 < elo1 && 0.0 < alpha && alpha < 1.0 && 0.0 < beta && beta < 1.0) {
+        return Err(format!("Invalid sprt parameters '{}'", s));
+    }
+    Ok(Sprt::new(elo0, elo1, alpha, beta))
+}
+
+fn parse_time_control(s: &str) -> Result<TimeControl, String> {
+    let (base, increment) = match s.split_once('+') {
+        Some((base, increment)) => (parse(base)?, parse(increment)?),
+        None => (parse(s)?, 0.0),
+    };
+    if !(base > 0.0 && increment >= 0.0) {
+        return Err(format!("Invalid time control '{}'", s));
+    }
+    Ok(TimeControl::per_game(
+        Duration::from_secs_f32(base),
+        Duration::from_secs_f32(increment),
+    ))
+}
+
+fn parse<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid value '{}'", s))
+}
@@ -32,13 +32,19 @@ where
     fn is_available_move(&self, mv: Self::Move) -> bool;
 
     /// Pick a random move from the `available_moves` with a uniform distribution. Panics if this board is done.
-    /// Can be overridden for better performance.
+    /// The default implementation generates the moves only once using reservoir sampling,
+    /// it can be overridden for better performance.
     fn random_available_move(&self, rng: &mut impl Rng) -> Self::Move {
-        let count = self.available_moves().count();
-        let index = rng.gen_range(0..count);
-        // SAFETY: unwrap is safe because the index is less than the
-        // length of the iterator.
-        self.available_moves().nth(index).unwrap()
+        let mut count: u32 = 0;
+        let mut picked = None;
+        self.available_moves().for_each(|mv| {
+            count += 1;
+            // replace the picked move with probability 1/count, so each move ends up picked with equal probability
+            if rng.gen_range(0..count) == 0 {
+                picked = Some(mv);
+            }
+        });
+        picked.expect("Board must have at least one available move")
     }
 
     /// Play the move `mv`, modifying this board.
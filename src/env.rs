@@ -0,0 +1,76 @@
+//! A [gym](https://github.com/openai/gym)-style environment, to train agents against the bots in this crate.
+//!
+//! The agent plays one side of the game and the opponent, any [Bot], plays the other side.
+//! The observation is the board itself, converting it to the tensors a learning framework expects is left to the
+//! caller. The reward is `1` for a win, `-1` for a loss and `0` otherwise, it is only nonzero when the game ends.
+use crate::ai::Bot;
+use crate::board::{Board, Outcome, Player};
+
+#[derive(Debug)]
+pub struct Env<B: Board, T: Bot<B>> {
+    start: B,
+    board: B,
+    opponent: T,
+    agent: Player,
+}
+
+impl<B: Board, T: Bot<B>> Env<B, T> {
+    /// Create an environment where the agent plays as `agent` against `opponent`, starting from `start`.
+    /// Call [reset](Env::reset) before the first [step](Env::step).
+    pub fn new(start: B, opponent: T, agent: Player) -> Self {
+        Env {
+            board: start.clone(),
+            start,
+            opponent,
+            agent,
+        }
+    }
+
+    /// The current board.
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// The player controlled by the agent.
+    pub fn agent(&self) -> Player {
+        self.agent
+    }
+
+    /// Start a new game, letting the opponent play until it's the agent's turn or the game is done.
+    pub fn reset(&mut self) -> B {
+        self.board = self.start.clone();
+        self.play_opponent();
+        self.board.clone()
+    }
+
+    /// Play `action` for the agent, followed by the opponent until it's the agent's turn again.
+    /// Returns the observation, the reward and whether the game is done.
+    ///
+    /// Panics if the game is done or if `action` is not available.
+    pub fn step(&mut self, action: B::Move) -> (B, f32, bool) {
+        assert!(!self.board.is_done(), "The game is done, call reset first");
+        assert_eq!(self.board.next_player(), self.agent);
+
+        self.board.play(action);
+        self.play_opponent();
+
+        let reward = match self.board.outcome() {
+            None | Some(Outcome::Draw) => 0.0,
+            Some(Outcome::WonBy(player)) => {
+                if player == self.agent {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+        (self.board.clone(), reward, self.board.is_done())
+    }
+
+    fn play_opponent(&mut self) {
+        while !self.board.is_done() && self.board.next_player() != self.agent {
+            let mv = self.opponent.select_move(&self.board);
+            self.board.play(mv);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
 use internal_iterator::InternalIterator;
 use rand::Rng;
@@ -6,10 +7,20 @@ use rand::Rng;
 use crate::board::{Board, BoardAvailableMoves, Outcome, Player};
 use crate::games::ataxx::{Coord, Move, Tiles};
 use crate::symmetry::D4Symmetry;
+use crate::util::zobrist::zobrist_keys;
 
 const MAX_MOVES_SINCE_LAST_COPY: u8 = 100;
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+/// Zobrist keys for a tile of player A, a tile of player B and a gap at each sparse index, followed by the key for
+/// player B to move.
+static ZOBRIST: [u64; 3 * 64 + 1] = zobrist_keys(0xA7A8);
+const ZOBRIST_B_TO_MOVE: usize = 3 * 64;
+
+fn tile_key(kind: usize, coord: Coord) -> u64 {
+    ZOBRIST[kind * 64 + coord.sparse_i() as usize]
+}
+
+#[derive(Clone, Eq, PartialEq)]
 pub struct AtaxxBoard {
     pub(super) tiles_a: Tiles,
     pub(super) tiles_b: Tiles,
@@ -17,18 +28,30 @@ pub struct AtaxxBoard {
     pub(super) moves_since_last_copy: u8,
     pub(super) next_player: Player,
     pub(super) outcome: Option<Outcome>,
+    /// The Zobrist hash of the tiles, gaps and next player, kept up to date by [Board::play].
+    pub(super) hash: u64,
 }
 
 impl Default for AtaxxBoard {
     fn default() -> Self {
-        AtaxxBoard {
+        let mut board = AtaxxBoard {
             tiles_a: Tiles::CORNERS_A,
             tiles_b: Tiles::CORNERS_B,
             gaps: Tiles::empty(),
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: None,
-        }
+            hash: 0,
+        };
+        board.update_hash();
+        board
+    }
+}
+
+/// Only hashes the Zobrist hash, which is consistent with [Eq] and doesn't need to look at the tiles.
+impl Hash for AtaxxBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state)
     }
 }
 
@@ -41,6 +64,7 @@ impl AtaxxBoard {
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: Some(Outcome::Draw),
+            hash: 0,
         }
     }
 
@@ -70,6 +94,47 @@ impl AtaxxBoard {
         !(self.tiles_a | self.tiles_b | self.gaps)
     }
 
+    /// The Zobrist hash of the tiles, gaps and next player. This is updated incrementally when moves are played,
+    /// so it's free to call. The move counter is not included, boards with the same hash are repetitions.
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// Compute the Zobrist hash from scratch, see [AtaxxBoard::zobrist].
+    pub fn compute_zobrist(&self) -> u64 {
+        let mut hash = 0;
+        for (kind, tiles) in [self.tiles_a, self.tiles_b, self.gaps].iter().enumerate() {
+            for coord in *tiles {
+                hash ^= tile_key(kind, coord);
+            }
+        }
+        if self.next_player == Player::B {
+            hash ^= ZOBRIST[ZOBRIST_B_TO_MOVE];
+        }
+        hash
+    }
+
+    /// Recompute the hash after the tiles or next player have been changed directly.
+    pub(super) fn update_hash(&mut self) {
+        self.hash = self.compute_zobrist();
+    }
+
+    /// This board with `count` tiles of `player` removed, starting from the lowest coordinates.
+    /// Removing all tiles of a player loses the game for them.
+    pub fn with_handicap(&self, player: Player, count: u8) -> AtaxxBoard {
+        let mut next = self.clone();
+        let tiles = match player {
+            Player::A => &mut next.tiles_a,
+            Player::B => &mut next.tiles_b,
+        };
+        for coord in tiles.into_iter().take(count as usize).collect::<Vec<_>>() {
+            *tiles = tiles.clear(coord);
+        }
+        next.update_outcome();
+        next.update_hash();
+        next
+    }
+
     /// Return whether the player with the given tiles has to pass, ie. cannot make a copy or jump move.
     fn must_pass(&self, tiles: Tiles) -> bool {
         let possible_targets = tiles.copy_targets() | tiles.jump_targets();
@@ -188,9 +253,11 @@ impl Board for AtaxxBoard {
     }
 
     fn play(&mut self, mv: Self::Move) {
-        assert!(self.is_available_move(mv), "{:?} is not available", mv);
+        assert!(self.is_available_move(mv), "{} is not available", mv);
 
-        let (next_tiles, other_tiles) = self.tiles_pov_mut();
+        let next_kind = self.next_player.index() as usize;
+        self.hash ^= ZOBRIST[ZOBRIST_B_TO_MOVE];
+        let next_tiles = self.tiles_pov_mut().0;
 
         let to = match mv {
             Move::Pass => {
@@ -202,15 +269,22 @@ impl Board for AtaxxBoard {
             Move::Copy { to } => to,
             Move::Jump { from, to } => {
                 *next_tiles &= !Tiles::coord(from);
+                self.hash ^= tile_key(next_kind, from);
                 to
             }
         };
 
-        let to = Tiles::coord(to);
-        let converted = *other_tiles & to.copy_targets();
-        *next_tiles |= to | converted;
+        let (next_tiles, other_tiles) = self.tiles_pov_mut();
+        let to_tiles = Tiles::coord(to);
+        let converted = *other_tiles & to_tiles.copy_targets();
+        *next_tiles |= to_tiles | converted;
         *other_tiles &= !converted;
 
+        self.hash ^= tile_key(next_kind, to);
+        for coord in converted {
+            self.hash ^= tile_key(0, coord) ^ tile_key(1, coord);
+        }
+
         self.moves_since_last_copy += 1;
         if let Move::Copy { .. } = mv {
             self.moves_since_last_copy = 0;
@@ -225,14 +299,17 @@ impl Board for AtaxxBoard {
     }
 
     fn map(&self, sym: Self::Symmetry) -> Self {
-        AtaxxBoard {
+        let mut board = AtaxxBoard {
             tiles_a: self.tiles_a.map(sym),
             tiles_b: self.tiles_b.map(sym),
             gaps: self.gaps.map(sym),
             moves_since_last_copy: self.moves_since_last_copy,
             next_player: self.next_player,
             outcome: self.outcome,
-        }
+            hash: 0,
+        };
+        board.update_hash();
+        board
     }
 
     fn map_move(sym: Self::Symmetry, mv: Self::Move) -> Self::Move {
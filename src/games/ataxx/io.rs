@@ -2,8 +2,10 @@ use std::fmt::{Debug, Display, Formatter, Write};
 
 use regex::Regex;
 
+use crate::ai::external::ExternalBoard;
 use crate::board::Player;
-use crate::games::ataxx::{AtaxxBoard, Coord, Tiles};
+use crate::games::ataxx::{AtaxxBoard, Coord, Move, Tiles};
+use crate::util::render::{Cell, RenderBoard};
 
 const FEN_REGEX: &str = r"(?x)(?-u)
     ^ ([ox\-\d]+)/([ox\-\d]+)/([ox\-\d]+)/([ox\-\d]+)/([ox\-\d]+)/([ox\-\d]+)/([ox\-\d]+)
@@ -11,11 +13,17 @@ const FEN_REGEX: &str = r"(?x)(?-u)
 ";
 
 impl AtaxxBoard {
+    /// Parse a board from its FEN representation, panics if `fen` is invalid.
     pub fn from_fen(fen: &str) -> AtaxxBoard {
+        AtaxxBoard::try_from_fen(fen).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Parse a board from its FEN representation.
+    pub fn try_from_fen(fen: &str) -> Result<AtaxxBoard, String> {
         let mut board = AtaxxBoard::empty();
 
         let regex = Regex::new(FEN_REGEX).unwrap();
-        let captures = regex.captures(fen).unwrap_or_else(|| panic!("Invalid fen {:?}", fen));
+        let captures = regex.captures(fen).ok_or_else(|| format!("Invalid fen {:?}", fen))?;
         assert_eq!(1 + 7 + 3, captures.len());
 
         for y in (0..7).rev() {
@@ -23,7 +31,7 @@ impl AtaxxBoard {
             let mut x = 0;
             for c in line.chars() {
                 if x >= 7 {
-                    panic!("Line {:?} too long", line)
+                    return Err(format!("Line {:?} too long", line));
                 }
                 let tiles = Tiles::coord(Coord::from_xy(x, y as u8));
                 match c {
@@ -38,7 +46,9 @@ impl AtaxxBoard {
                 }
                 x += 1;
             }
-            assert_eq!(x, 7, "Line {:?} too short", line);
+            if x != 7 {
+                return Err(format!("Line {:?} too short", line));
+            }
         }
 
         board.next_player = match &captures["next"] {
@@ -46,10 +56,13 @@ impl AtaxxBoard {
             "o" => Player::B,
             _ => unreachable!(),
         };
-        board.moves_since_last_copy = captures["half"].parse::<u8>().unwrap();
+        board.moves_since_last_copy = captures["half"]
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid halfmove clock in fen {:?}", fen))?;
 
         board.update_outcome();
-        board
+        board.update_hash();
+        Ok(board)
     }
 
     pub fn to_fen(&self) -> String {
@@ -150,3 +163,49 @@ fn player_symbol(player: Player) -> char {
         Player::B => 'o',
     }
 }
+
+impl ExternalBoard for AtaxxBoard {
+    fn external_start_pos() -> Self {
+        AtaxxBoard::default()
+    }
+
+    fn external_fen(&self) -> String {
+        self.to_fen()
+    }
+
+    fn external_from_fen(fen: &str) -> Option<Self> {
+        AtaxxBoard::try_from_fen(fen).ok()
+    }
+
+    fn external_format_move(&self, mv: Self::Move) -> String {
+        mv.to_string()
+    }
+
+    fn external_parse_move(&self, s: &str) -> Option<Self::Move> {
+        s.parse().ok()
+    }
+}
+
+impl RenderBoard for AtaxxBoard {
+    fn render_size(&self) -> (u8, u8) {
+        (7, 7)
+    }
+
+    fn render_cell(&self, x: u8, y: u8) -> Cell {
+        let coord = Coord::from_xy(x, 6 - y);
+        match self.tile(coord) {
+            Some(player) => Cell::Stone(player),
+            None if self.gaps.has(coord) => Cell::Blocked,
+            None => Cell::Empty,
+        }
+    }
+
+    fn render_move_cells(&self, mv: Self::Move) -> Vec<(u8, u8)> {
+        let cell = |coord: Coord| (coord.x(), 6 - coord.y());
+        match mv {
+            Move::Pass => vec![],
+            Move::Copy { to } => vec![cell(to)],
+            Move::Jump { from, to } => vec![cell(from), cell(to)],
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::cmp::max;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 
 use crate::games::ataxx::tiles::Tiles;
 use crate::symmetry::D4Symmetry;
@@ -16,13 +17,13 @@ pub enum Move {
 
 impl Debug for Coord {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_uai())
+        write!(f, "{}", self)
     }
 }
 
 impl Debug for Move {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_uai())
+        write!(f, "{}", self)
     }
 }
 
@@ -80,35 +81,67 @@ fn abs_distance(a: u8, b: u8) -> u8 {
 
 impl Coord {
     pub fn to_uai(self) -> String {
-        format!("{}{}", (b'a' + self.x()) as char, self.y() + 1)
+        self.to_string()
     }
 
     pub fn from_uai(s: &str) -> Coord {
-        assert_eq!(s.len(), 2);
-        let x = s.as_bytes()[0] - b'a';
-        let y = (s.as_bytes()[1] - b'0') - 1;
-        Coord::from_xy(x, y)
+        s.parse().unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl Move {
     pub fn to_uai(self) -> String {
-        match self {
-            Move::Pass => "0000".to_string(),
-            Move::Copy { to } => to.to_uai(),
-            Move::Jump { from, to } => format!("{}{}", from.to_uai(), to.to_uai()),
-        }
+        self.to_string()
     }
 
     pub fn from_uai(s: &str) -> Move {
-        match s {
-            "0000" => Move::Pass,
-            _ if s.len() == 2 => Move::Copy { to: Coord::from_uai(s) },
-            _ if s.len() == 4 => Move::Jump {
-                from: Coord::from_uai(&s[..2]),
-                to: Coord::from_uai(&s[2..]),
-            },
-            _ => panic!("Invalid move uai string '{}'", s),
+        s.parse().unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Display for Coord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", (b'a' + self.x()) as char, self.y() + 1)
+    }
+}
+
+/// The notation used by other Ataxx engines: a single square for copies, `fromto` for jumps and `0000` for pass.
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Move::Pass => write!(f, "0000"),
+            Move::Copy { to } => write!(f, "{}", to),
+            Move::Jump { from, to } => write!(f, "{}{}", from, to),
+        }
+    }
+}
+
+impl FromStr for Coord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match *s.as_bytes() {
+            [x @ b'a'..=b'g', y @ b'1'..=b'7'] => Ok(Coord::from_xy(x - b'a', y - b'1')),
+            _ => Err(format!("Invalid coord '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("Invalid move '{}'", s);
+        match s.len() {
+            _ if s == "0000" => Ok(Move::Pass),
+            2 => Ok(Move::Copy {
+                to: s.parse().map_err(|_| err())?,
+            }),
+            4 if s.is_char_boundary(2) => Ok(Move::Jump {
+                from: s[..2].parse().map_err(|_| err())?,
+                to: s[2..].parse().map_err(|_| err())?,
+            }),
+            _ => Err(err()),
         }
     }
 }
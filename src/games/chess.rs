@@ -1,12 +1,16 @@
 use std::fmt::Write;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-use chess::{BoardStatus, ChessMove, Color, MoveGen, Piece};
+use chess::{BoardStatus, ChessMove, Color, File, MoveGen, Piece, Rank, Square};
 use internal_iterator::{Internal, InternalIterator, IteratorExt};
+use itertools::Itertools;
 use rand::Rng;
 
+use crate::ai::external::ExternalBoard;
 use crate::board::{Board, BoardAvailableMoves, Outcome, Player};
 use crate::symmetry::UnitSymmetry;
+use crate::util::render::{Cell, RenderBoard};
 
 pub const MAX_REVERSIBLE_MOVES: u32 = 100;
 
@@ -15,13 +19,16 @@ pub struct ChessBoard {
     inner: chess::Board,
     /// The number of consecutive reversible moves, resets when an irreversible move is played.
     reversible_moves: u32,
+    /// The fullmove number, starts at 1 and is incremented after each move of black.
+    full_moves: u32,
 }
 
 impl ChessBoard {
-    pub fn new(inner: chess::Board, reversible_moves: u32) -> Self {
+    pub fn new(inner: chess::Board, reversible_moves: u32, full_moves: u32) -> Self {
         ChessBoard {
             inner,
             reversible_moves,
+            full_moves,
         }
     }
 
@@ -31,6 +38,14 @@ impl ChessBoard {
     pub fn reversible_moves(&self) -> u32 {
         self.reversible_moves
     }
+    pub fn full_moves(&self) -> u32 {
+        self.full_moves
+    }
+
+    /// The hash of the position that ignores the move counter, boards with the same key are repetitions.
+    pub fn repetition_key(&self) -> u64 {
+        self.inner.get_hash()
+    }
 }
 
 impl Board for ChessBoard {
@@ -75,9 +90,15 @@ impl Board for ChessBoard {
             self.reversible_moves + 1
         };
 
+        let full_moves = match self.inner.side_to_move() {
+            Color::White => self.full_moves,
+            Color::Black => self.full_moves + 1,
+        };
+
         ChessBoard {
             inner: self.inner.make_move_new(mv),
             reversible_moves,
+            full_moves,
         }
     }
 
@@ -155,6 +176,7 @@ impl Default for ChessBoard {
         ChessBoard {
             inner: chess::Board::default(),
             reversible_moves: 0,
+            full_moves: 1,
         }
     }
 }
@@ -169,6 +191,104 @@ impl Display for ChessBoard {
     }
 }
 
+/// Convert a move to [standard algebraic notation](https://en.wikipedia.org/wiki/Algebraic_notation_(chess)),
+/// the notation used in PGN files. Panics if the move is not available.
+pub fn move_to_san(board: &ChessBoard, mv: ChessMove) -> String {
+    assert!(board.is_available_move(mv), "{} is not available", mv);
+
+    let inner = board.inner();
+    let source = mv.get_source();
+    let dest = mv.get_dest();
+    // SAFETY: unwrap is safe because the move is available, so there must be a piece on the source square.
+    let piece = inner.piece_on(source).unwrap();
+
+    let mut result = String::new();
+    let file_distance = (source.get_file().to_index() as i32 - dest.get_file().to_index() as i32).abs();
+
+    if piece == Piece::King && file_distance == 2 {
+        result.push_str(if dest.get_file() == File::G { "O-O" } else { "O-O-O" });
+    } else {
+        // pawns moving diagonally are always captures, including en passant
+        let capture = inner.piece_on(dest).is_some() || (piece == Piece::Pawn && file_distance != 0);
+
+        if piece == Piece::Pawn {
+            if capture {
+                result.push(file_char(source.get_file()));
+            }
+        } else {
+            result.push_str(&piece.to_string(Color::White));
+
+            // disambiguate between pieces of the same type that can move to the same square
+            let others = MoveGen::new_legal(inner)
+                .filter(|other| {
+                    other.get_dest() == dest
+                        && other.get_source() != source
+                        && inner.piece_on(other.get_source()) == Some(piece)
+                })
+                .collect_vec();
+            if !others.is_empty() {
+                let same_file = others.iter().any(|o| o.get_source().get_file() == source.get_file());
+                let same_rank = others.iter().any(|o| o.get_source().get_rank() == source.get_rank());
+
+                if !same_file || same_rank {
+                    result.push(file_char(source.get_file()));
+                }
+                if same_file {
+                    result.push(rank_char(source.get_rank()));
+                }
+            }
+        }
+
+        if capture {
+            result.push('x');
+        }
+        result.push(file_char(dest.get_file()));
+        result.push(rank_char(dest.get_rank()));
+
+        if let Some(promotion) = mv.get_promotion() {
+            result.push('=');
+            result.push_str(&promotion.to_string(Color::White));
+        }
+    }
+
+    let next = inner.make_move_new(mv);
+    if next.status() == BoardStatus::Checkmate {
+        result.push('#');
+    } else if next.checkers().popcnt() != 0 {
+        result.push('+');
+    }
+
+    result
+}
+
+/// Parse a move in standard algebraic notation, returns `None` if the move is invalid or not available.
+///
+/// This is tolerant: check and annotation suffixes are optional, castling can be written with zeros and
+/// coordinate notation as used by UCI is accepted too.
+pub fn move_from_san(board: &ChessBoard, san: &str) -> Option<ChessMove> {
+    fn strip(san: &str) -> &str {
+        let san = san.trim_end_matches(&['+', '#', '!', '?'][..]);
+        match san {
+            "0-0" => "O-O",
+            "0-0-0" => "O-O-O",
+            _ => san,
+        }
+    }
+
+    let san = strip(san);
+    let found = MoveGen::new_legal(board.inner()).find(|&mv| strip(&move_to_san(board, mv)) == san);
+
+    found.or_else(|| board.external_parse_move(san).filter(|&mv| board.is_available_move(mv)))
+}
+
+fn file_char(file: File) -> char {
+    (b'a' + file.to_index() as u8) as char
+}
+
+fn rank_char(rank: Rank) -> char {
+    (b'1' + rank.to_index() as u8) as char
+}
+
 pub fn moves_to_pgn(moves: &[ChessMove]) -> String {
     let mut result = String::new();
     let f = &mut result;
@@ -188,3 +308,94 @@ pub fn moves_to_pgn(moves: &[ChessMove]) -> String {
 
     result
 }
+
+impl ExternalBoard for ChessBoard {
+    fn external_start_pos() -> Self {
+        ChessBoard::default()
+    }
+
+    fn external_fen(&self) -> String {
+        // the inner board does not track the move counters
+        let fen = self.inner.to_string();
+        let fields = fen.split_whitespace().take(4).join(" ");
+        format!("{} {} {}", fields, self.reversible_moves, self.full_moves)
+    }
+
+    fn external_from_fen(fen: &str) -> Option<Self> {
+        let inner = chess::Board::from_str(fen).ok()?;
+        // the halfmove clock and fullmove number are the last two fields and may be omitted
+        let counter = |index: usize, default: u32| match fen.split_whitespace().nth(index) {
+            Some(counter) => counter.parse().ok(),
+            None => Some(default),
+        };
+        Some(ChessBoard::new(inner, counter(4, 0)?, counter(5, 1)?))
+    }
+
+    fn external_parse_move(&self, s: &str) -> Option<Self::Move> {
+        let b = s.as_bytes();
+        if b.len() != 4 && b.len() != 5 {
+            return None;
+        }
+
+        let square = |file: u8, rank: u8| {
+            if (b'a'..=b'h').contains(&file) && (b'1'..=b'8').contains(&rank) {
+                let rank = Rank::from_index((rank - b'1') as usize);
+                let file = File::from_index((file - b'a') as usize);
+                Some(Square::make_square(rank, file))
+            } else {
+                None
+            }
+        };
+
+        let promotion = match b.get(4) {
+            None => None,
+            Some(b'q') => Some(Piece::Queen),
+            Some(b'r') => Some(Piece::Rook),
+            Some(b'b') => Some(Piece::Bishop),
+            Some(b'n') => Some(Piece::Knight),
+            Some(_) => return None,
+        };
+
+        Some(ChessMove::new(square(b[0], b[1])?, square(b[2], b[3])?, promotion))
+    }
+
+    fn external_format_move(&self, mv: Self::Move) -> String {
+        let mut result = format!("{}{}", mv.get_source(), mv.get_dest());
+        if let Some(promotion) = mv.get_promotion() {
+            result.push_str(&promotion.to_string(Color::Black));
+        }
+        result
+    }
+}
+
+impl RenderBoard for ChessBoard {
+    fn render_size(&self) -> (u8, u8) {
+        (8, 8)
+    }
+
+    fn render_cell(&self, x: u8, y: u8) -> Cell {
+        let square = Square::make_square(Rank::from_index(7 - y as usize), File::from_index(x as usize));
+        match (self.inner.piece_on(square), self.inner.color_on(square)) {
+            (Some(piece), Some(color)) => Cell::Piece(piece_symbol(piece, color)),
+            _ => Cell::Empty,
+        }
+    }
+
+    fn render_move_cells(&self, mv: Self::Move) -> Vec<(u8, u8)> {
+        let cell = |square: Square| {
+            (
+                square.get_file().to_index() as u8,
+                7 - square.get_rank().to_index() as u8,
+            )
+        };
+        vec![cell(mv.get_source()), cell(mv.get_dest())]
+    }
+}
+
+fn piece_symbol(piece: Piece, color: Color) -> char {
+    let symbols = match color {
+        Color::White => ['♙', '♘', '♗', '♖', '♕', '♔'],
+        Color::Black => ['♟', '♞', '♝', '♜', '♛', '♚'],
+    };
+    symbols[piece.to_index()]
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
 
 use internal_iterator::{Internal, InternalIterator, IteratorExt};
 use itertools::Itertools;
@@ -8,11 +9,18 @@ use rand::Rng;
 use crate::board::{Board, BoardAvailableMoves, Outcome, Player};
 use crate::symmetry::D4Symmetry;
 use crate::util::bits::{get_nth_set_bit, BitIter};
+use crate::util::render::{Cell, RenderBoard};
+use crate::util::zobrist::zobrist_keys;
+
+/// Zobrist keys for a tile of each player, for each possible macro mask and for player B to move.
+static ZOBRIST: [u64; 2 * 81 + 512 + 1] = zobrist_keys(0x5777);
+const ZOBRIST_MACRO_MASK: usize = 2 * 81;
+const ZOBRIST_B_TO_MOVE: usize = 2 * 81 + 512;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Coord(u8);
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct STTTBoard {
     grids: [u32; 9],
     main_grid: u32,
@@ -23,6 +31,8 @@ pub struct STTTBoard {
 
     macro_mask: u32,
     macro_open: u32,
+
+    hash: u64,
 }
 
 impl Default for STTTBoard {
@@ -35,10 +45,18 @@ impl Default for STTTBoard {
             outcome: None,
             macro_mask: STTTBoard::FULL_MASK,
             macro_open: STTTBoard::FULL_MASK,
+            hash: ZOBRIST[ZOBRIST_MACRO_MASK + STTTBoard::FULL_MASK as usize],
         }
     }
 }
 
+/// Only hashes the Zobrist hash, which is consistent with [Eq] and doesn't need to look at the grids.
+impl Hash for STTTBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state)
+    }
+}
+
 impl STTTBoard {
     const FULL_MASK: u32 = 0b111_111_111;
 
@@ -56,6 +74,26 @@ impl STTTBoard {
         has_bit(self.macro_open, om)
     }
 
+    /// The Zobrist hash of the tiles, the macro mask and the next player.
+    /// This is updated incrementally when moves are played, so it's free to call.
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// Compute the Zobrist hash from scratch, see [STTTBoard::zobrist].
+    pub fn compute_zobrist(&self) -> u64 {
+        let mut hash = ZOBRIST[ZOBRIST_MACRO_MASK + self.macro_mask as usize];
+        for coord in Coord::all() {
+            if let Some(player) = self.tile(coord) {
+                hash ^= ZOBRIST[81 * player.index() as usize + coord.o() as usize];
+            }
+        }
+        if self.next_player == Player::B {
+            hash ^= ZOBRIST[ZOBRIST_B_TO_MOVE];
+        }
+        hash
+    }
+
     /// Return the number of non-empty tiles.
     pub fn count_tiles(&self) -> u32 {
         self.grids.iter().map(|tile| tile.count_ones()).sum()
@@ -69,6 +107,7 @@ impl STTTBoard {
         //set tile and macro, check win
         let new_grid = self.grids[om as usize] | (1 << (os + p));
         self.grids[om as usize] = new_grid;
+        self.hash ^= ZOBRIST[81 * player.index() as usize + coord.o() as usize];
 
         let grid_win = is_win_grid((new_grid >> p) & STTTBoard::FULL_MASK);
         if grid_win {
@@ -87,7 +126,10 @@ impl STTTBoard {
                 self.outcome = Some(Outcome::Draw);
             }
         }
-        self.macro_mask = self.calc_macro_mask(os);
+        let macro_mask = self.calc_macro_mask(os);
+        self.hash ^= ZOBRIST[ZOBRIST_MACRO_MASK + self.macro_mask as usize];
+        self.hash ^= ZOBRIST[ZOBRIST_MACRO_MASK + macro_mask as usize];
+        self.macro_mask = macro_mask;
     }
 
     fn calc_macro_mask(&self, os: u8) -> u32 {
@@ -118,27 +160,24 @@ impl Board for STTTBoard {
     }
 
     fn random_available_move(&self, rng: &mut impl Rng) -> Self::Move {
-        // TODO we can also implement size_hint and skip for the available move iterator,
-        //   then we don't need this complicated body any more
-
         assert!(!self.is_done(), "Board must not be done");
 
+        // collect the free tiles of the available grids once, then pick the grid and tile with popcounts
+        let mut free_grids = [0; 9];
         let mut count = 0;
         for om in BitIter::new(self.macro_mask) {
-            count += 9 - self.grids[om as usize].count_ones();
+            let free_grid = !compact_grid(self.grids[om as usize]) & STTTBoard::FULL_MASK;
+            free_grids[om as usize] = free_grid;
+            count += free_grid.count_ones();
         }
 
         let mut index = rng.gen_range(0..count);
 
-        for om in BitIter::new(self.macro_mask) {
-            let grid = self.grids[om as usize];
-            let grid_count = 9 - grid.count_ones();
-
+        for (om, &free_grid) in free_grids.iter().enumerate() {
+            let grid_count = free_grid.count_ones();
             if index < grid_count {
-                let os = get_nth_set_bit(!compact_grid(grid), index);
-                return Coord::from_oo(om, os);
+                return Coord::from_oo(om as u8, get_nth_set_bit(free_grid, index));
             }
-
             index -= grid_count;
         }
 
@@ -154,7 +193,8 @@ impl Board for STTTBoard {
 
         //update for next player
         self.last_move = Some(mv);
-        self.next_player = self.next_player.other()
+        self.next_player = self.next_player.other();
+        self.hash ^= ZOBRIST[ZOBRIST_B_TO_MOVE];
     }
 
     fn outcome(&self) -> Option<Outcome> {
@@ -167,7 +207,7 @@ impl Board for STTTBoard {
             grids[map_oo(sym, oo) as usize] = map_grid(sym, self.grids[oo as usize])
         }
 
-        STTTBoard {
+        let mut board = STTTBoard {
             grids,
             main_grid: map_grid(sym, self.main_grid),
             last_move: self.last_move.map(|c| Self::map_move(sym, c)),
@@ -175,7 +215,10 @@ impl Board for STTTBoard {
             outcome: self.outcome,
             macro_mask: map_grid(sym, self.macro_mask),
             macro_open: map_grid(sym, self.macro_open),
-        }
+            hash: 0,
+        };
+        board.hash = board.compute_zobrist();
+        board
     }
 
     fn map_move(sym: D4Symmetry, mv: Coord) -> Coord {
@@ -399,6 +442,28 @@ impl fmt::Display for STTTBoard {
     }
 }
 
+impl RenderBoard for STTTBoard {
+    fn render_size(&self) -> (u8, u8) {
+        (9, 9)
+    }
+
+    fn render_cell(&self, x: u8, y: u8) -> Cell {
+        match self.tile(Coord::from_xy(x, y)) {
+            Some(Player::A) => Cell::Piece('X'),
+            Some(Player::B) => Cell::Piece('O'),
+            None => Cell::Empty,
+        }
+    }
+
+    fn render_move_cells(&self, mv: Self::Move) -> Vec<(u8, u8)> {
+        vec![(mv.x(), mv.y())]
+    }
+
+    fn render_block_size(&self) -> Option<u8> {
+        Some(3)
+    }
+}
+
 pub fn board_to_compact_string(board: &STTTBoard) -> String {
     Coord::all().map(|coord| symbol_from_tile(board, coord)).join("")
 }
@@ -427,7 +492,8 @@ pub fn board_from_compact_string(s: &str) -> STTTBoard {
     if let Some((last_player, last_coord)) = last_move {
         board.set_tile_and_update(last_player, last_coord);
         board.last_move = Some(last_coord);
-        board.next_player = last_player.other()
+        board.next_player = last_player.other();
+        board.hash = board.compute_zobrist();
     }
 
     board
@@ -1,7 +1,7 @@
-use crate::ai::minimax::Heuristic;
+use crate::ai::minimax::{Heuristic, IncrementalHeuristic};
 use crate::ai::solver::SolverHeuristic;
-use crate::board::Board;
-use crate::games::ataxx::{AtaxxBoard, Tiles};
+use crate::board::{Board, Player};
+use crate::games::ataxx::{AtaxxBoard, Coord, Move, Tiles};
 
 #[derive(Debug)]
 pub struct AtaxxTileHeuristic {
@@ -60,3 +60,96 @@ impl Heuristic<AtaxxBoard> for AtaxxTileHeuristic {
         }
     }
 }
+
+/// A piece-square table heuristic: each tile is worth the weight of its square.
+/// The sum of the weights of each player is updated incrementally, see [IncrementalHeuristic].
+#[derive(Debug)]
+pub struct AtaxxSquareHeuristic {
+    /// The weight of each square, indexed by [Coord::dense_i].
+    weights: [i32; 49],
+}
+
+impl AtaxxSquareHeuristic {
+    pub fn new(weights: [i32; 49]) -> Self {
+        AtaxxSquareHeuristic { weights }
+    }
+
+    fn weight_sum(&self, tiles: Tiles) -> i32 {
+        tiles
+            .into_iter()
+            .map(|coord| self.weights[coord.dense_i() as usize])
+            .sum()
+    }
+
+    /// The change in weight sum for each player from `board` to `child`.
+    fn delta(&self, board: &AtaxxBoard, child: &AtaxxBoard) -> [i32; 2] {
+        let delta = |before: Tiles, after: Tiles| self.weight_sum(after & !before) - self.weight_sum(before & !after);
+        [
+            delta(board.tiles_a(), child.tiles_a()),
+            delta(board.tiles_b(), child.tiles_b()),
+        ]
+    }
+}
+
+impl Default for AtaxxSquareHeuristic {
+    /// Corners and edges are worth more since they can be captured from fewer squares.
+    fn default() -> Self {
+        let mut weights = [0; 49];
+        for coord in Coord::all() {
+            let edges = [coord.x() == 0, coord.x() == 6, coord.y() == 0, coord.y() == 6];
+            let edge_count = edges.iter().filter(|&&e| e).count() as i32;
+            weights[coord.dense_i() as usize] = 100 + 15 * edge_count;
+        }
+        AtaxxSquareHeuristic { weights }
+    }
+}
+
+impl IncrementalHeuristic<AtaxxBoard> for AtaxxSquareHeuristic {
+    type V = i32;
+    /// The weight sum of player A and player B.
+    type Accumulator = [i32; 2];
+
+    fn bound(&self) -> Self::V {
+        i32::MAX
+    }
+
+    fn accumulator(&self, board: &AtaxxBoard) -> Self::Accumulator {
+        [self.weight_sum(board.tiles_a()), self.weight_sum(board.tiles_b())]
+    }
+
+    fn play(&self, acc: &mut Self::Accumulator, board: &AtaxxBoard, _: Move, child: &AtaxxBoard) {
+        let [delta_a, delta_b] = self.delta(board, child);
+        acc[0] += delta_a;
+        acc[1] += delta_b;
+    }
+
+    fn undo(&self, acc: &mut Self::Accumulator, board: &AtaxxBoard, _: Move, child: &AtaxxBoard) {
+        let [delta_a, delta_b] = self.delta(board, child);
+        acc[0] -= delta_a;
+        acc[1] -= delta_b;
+    }
+
+    fn value(&self, acc: &Self::Accumulator, board: &AtaxxBoard, length: u32) -> Self::V {
+        if board.is_done() {
+            // return near-max values for wins/draws/losses
+            SolverHeuristic.value(board, length)
+        } else {
+            match board.next_player() {
+                Player::A => acc[0] - acc[1],
+                Player::B => acc[1] - acc[0],
+            }
+        }
+    }
+}
+
+impl Heuristic<AtaxxBoard> for AtaxxSquareHeuristic {
+    type V = i32;
+
+    fn bound(&self) -> Self::V {
+        i32::MAX
+    }
+
+    fn value(&self, board: &AtaxxBoard, length: u32) -> Self::V {
+        IncrementalHeuristic::value(self, &self.accumulator(board), board, length)
+    }
+}
@@ -26,9 +26,19 @@
 //!     * [MCTSBot](crate::ai::mcts::MCTSBot),
 //!         which picks the best move as found by [Monte Carlo Tree Search](https://en.wikipedia.org/wiki/Monte_Carlo_tree_search).
 //! * Random board generation functions, see [board_gen](crate::util::board_gen).
-//! * A bot vs bot game runner to compare playing strength, see [bot_game](crate::util::bot_game).
+//! * A bot vs bot game runner to compare playing strength, see [bot_game](crate::util::bot_game),
+//!   and round-robin [tournament](crate::util::tournament)s between more bots.
+//! * A persistent Glicko-2 and Elo ladder to track the strength of bots across many matches, see [rating](crate::util::rating).
 //! * Simple game statistics (perft, random game length) which can be used to test [Board](crate::board::Board) implementations.
 //!
+//! # Cargo features
+//!
+//! * `native` (enabled by default): everything that needs threads, subprocesses or the system clock,
+//!   like [bot_game](crate::util::bot_game) and the UAI/UCI engine loops.
+//!   Disable it to compile for `wasm32-unknown-unknown`, for example to run the bots in a browser.
+//! * `websocket`: live streaming of bot games, see [websocket](crate::util::websocket).
+//! * `lichess`: a client to let bots play on lichess, see [online](crate::online).
+//!
 //! # Examples
 //!
 //! ## List the available moves on a board and play a random one.
@@ -77,4 +87,8 @@ pub mod heuristic;
 
 pub mod util;
 
+pub mod env;
+pub mod online;
+pub mod server;
 pub mod uai;
+pub mod uci;
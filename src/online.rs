@@ -0,0 +1,71 @@
+//! Let a [Bot] play unattended on online game servers.
+//!
+//! A server is accessed through an implementation of [OnlineClient], [play_online] then runs the loop that
+//! answers every game state where it's our turn with a move from the bot.
+//! The loop is blocking, clients that follow multiple games at once are expected to merge them into a single
+//! stream of events, for example by reading each game on its own thread.
+//!
+//! A client for the [lichess](https://lichess.org) bot API is available in [lichess] with the `lichess` feature.
+use std::collections::HashMap;
+
+use crate::ai::Bot;
+use crate::board::Board;
+
+#[cfg(feature = "lichess")]
+pub mod lichess;
+
+/// Something that happened on the server.
+#[derive(Debug, Clone)]
+pub enum ClientEvent<B: Board> {
+    /// The state of game `game_id` is now `board`. If `our_turn` a move should be submitted.
+    GameState { game_id: String, board: B, our_turn: bool },
+    /// Game `game_id` ended or was aborted.
+    GameEnd { game_id: String },
+}
+
+pub trait OnlineClient<B: Board> {
+    /// Log in to the server, returns the name of the account.
+    fn login(&mut self) -> Result<String, String>;
+
+    /// Block until the next event happens, returns `None` if the connection was closed.
+    fn next_event(&mut self) -> Result<Option<ClientEvent<B>>, String>;
+
+    /// Submit `mv` in game `game_id`.
+    fn submit_move(&mut self, game_id: &str, mv: B::Move) -> Result<(), String>;
+}
+
+/// Log in with `client` and play all games until the connection is closed.
+///
+/// A new bot is created with `new_bot` for every game. Returns the number of games that were played.
+pub fn play_online<B: Board, T: Bot<B>>(
+    client: &mut impl OnlineClient<B>,
+    mut new_bot: impl FnMut() -> T,
+) -> Result<u32, String> {
+    client.login()?;
+
+    let mut bots: HashMap<String, T> = HashMap::new();
+    let mut games_played = 0;
+
+    while let Some(event) = client.next_event()? {
+        match event {
+            ClientEvent::GameState {
+                game_id,
+                board,
+                our_turn,
+            } => {
+                let bot = bots.entry(game_id.clone()).or_insert_with(&mut new_bot);
+                if our_turn && !board.is_done() {
+                    let mv = bot.select_move(&board);
+                    client.submit_move(&game_id, mv)?;
+                }
+            }
+            ClientEvent::GameEnd { game_id } => {
+                if bots.remove(&game_id).is_some() {
+                    games_played += 1;
+                }
+            }
+        }
+    }
+
+    Ok(games_played)
+}
@@ -0,0 +1,249 @@
+//! A client for the [lichess bot API](https://lichess.org/api#tag/Bot), requires the `lichess` feature.
+//!
+//! The token must belong to a bot account and have the `bot:play` scope.
+//! Incoming challenges for standard chess are accepted, other variants are declined.
+use std::io::{BufRead, BufReader};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use serde_json::Value;
+
+use crate::ai::external::ExternalBoard;
+use crate::board::{Board, Player};
+use crate::games::chess::{ChessBoard, MAX_REVERSIBLE_MOVES};
+use crate::online::{ClientEvent, OnlineClient};
+
+pub const LICHESS_URL: &str = "https://lichess.org";
+
+#[derive(Debug)]
+pub struct LichessClient {
+    base_url: String,
+    token: String,
+    agent: ureq::Agent,
+
+    events: Option<Receiver<Result<ClientEvent<ChessBoard>, String>>>,
+}
+
+impl LichessClient {
+    /// Create a client for the server at `base_url`, usually [LICHESS_URL].
+    pub fn new(base_url: &str, token: &str) -> Self {
+        LichessClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            agent: ureq::AgentBuilder::new().build(),
+            events: None,
+        }
+    }
+}
+
+impl OnlineClient<ChessBoard> for LichessClient {
+    fn login(&mut self) -> Result<String, String> {
+        let account = get_json(&self.agent, &self.token, &format!("{}/api/account", self.base_url))?;
+        let account_id = account["id"]
+            .as_str()
+            .ok_or_else(|| format!("Unexpected account response {}", account))?
+            .to_string();
+
+        // follow the incoming events on a background thread
+        let (sender, receiver) = channel();
+        let api = Api {
+            base_url: self.base_url.clone(),
+            token: self.token.clone(),
+            agent: self.agent.clone(),
+            account_id: account_id.clone(),
+        };
+        std::thread::spawn(move || {
+            if let Err(e) = api.follow_events(&sender) {
+                let _ = sender.send(Err(e));
+            }
+        });
+
+        self.events = Some(receiver);
+        Ok(account_id)
+    }
+
+    fn next_event(&mut self) -> Result<Option<ClientEvent<ChessBoard>>, String> {
+        let events = self.events.as_ref().ok_or_else(|| "Not logged in".to_string())?;
+        match events.recv() {
+            Ok(event) => event.map(Some),
+            // all threads stopped, which means the event stream was closed
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn submit_move(&mut self, game_id: &str, mv: <ChessBoard as Board>::Move) -> Result<(), String> {
+        let url = format!("{}/api/bot/game/{}/move/{}", self.base_url, game_id, mv);
+        post(&self.agent, &self.token, &url)
+    }
+}
+
+/// The state shared by the threads that follow the event streams.
+#[derive(Clone)]
+struct Api {
+    base_url: String,
+    token: String,
+    agent: ureq::Agent,
+    account_id: String,
+}
+
+type EventSender = Sender<Result<ClientEvent<ChessBoard>, String>>;
+
+impl Api {
+    fn follow_events(&self, sender: &EventSender) -> Result<(), String> {
+        let url = format!("{}/api/stream/event", self.base_url);
+
+        for_each_line(&self.agent, &self.token, &url, |event| {
+            match event["type"].as_str() {
+                Some("challenge") => {
+                    let challenge = &event["challenge"];
+                    let id = challenge["id"].as_str().unwrap_or_default();
+                    let action = if challenge["variant"]["key"] == "standard" {
+                        "accept"
+                    } else {
+                        "decline"
+                    };
+                    post(
+                        &self.agent,
+                        &self.token,
+                        &format!("{}/api/challenge/{}/{}", self.base_url, id, action),
+                    )?;
+                }
+                Some("gameStart") => {
+                    let game = &event["game"];
+                    let game_id = game["gameId"]
+                        .as_str()
+                        .or_else(|| game["id"].as_str())
+                        .ok_or_else(|| format!("Unexpected event {}", event))?
+                        .to_string();
+
+                    let api = self.clone();
+                    let sender = sender.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = api.follow_game(&game_id, &sender) {
+                            let _ = sender.send(Err(e));
+                        }
+                    });
+                }
+                _ => {}
+            }
+            Ok(true)
+        })
+    }
+
+    fn follow_game(&self, game_id: &str, sender: &EventSender) -> Result<(), String> {
+        let url = format!("{}/api/bot/game/stream/{}", self.base_url, game_id);
+
+        let mut start = None;
+        let mut our_player = Player::A;
+
+        for_each_line(&self.agent, &self.token, &url, |event| {
+            let state = match event["type"].as_str() {
+                Some("gameFull") => {
+                    start = Some(match event["initialFen"].as_str() {
+                        None | Some("startpos") => ChessBoard::external_start_pos(),
+                        Some(fen) => {
+                            ChessBoard::external_from_fen(fen).ok_or_else(|| format!("Invalid fen '{}'", fen))?
+                        }
+                    });
+                    our_player = if event["white"]["id"] == self.account_id.as_str() {
+                        Player::A
+                    } else {
+                        Player::B
+                    };
+                    &event["state"]
+                }
+                Some("gameState") => event,
+                _ => return Ok(true),
+            };
+
+            let mut board = start
+                .clone()
+                .ok_or_else(|| "Game state received before the full game".to_string())?;
+            // whether the game is over is decided by the status lichess reports, not by the local outcome
+            for mv_str in state["moves"].as_str().unwrap_or_default().split_whitespace() {
+                board = without_move_limit(board);
+                let mv = board
+                    .external_parse_move(mv_str)
+                    .filter(|&mv| board.is_available_move(mv))
+                    .ok_or_else(|| format!("Invalid move '{}' in game {}", mv_str, game_id))?;
+                board.play(mv);
+            }
+            let board = without_move_limit(board);
+
+            let game_id = game_id.to_string();
+            let event = if state["status"] == "started" {
+                let our_turn = board.next_player() == our_player;
+                ClientEvent::GameState {
+                    game_id,
+                    board,
+                    our_turn,
+                }
+            } else {
+                ClientEvent::GameEnd { game_id }
+            };
+
+            let ended = matches!(event, ClientEvent::GameEnd { .. });
+            // stop if the receiver is gone
+            Ok(sender.send(Ok(event)).is_ok() && !ended)
+        })
+    }
+}
+
+/// Lichess does not end games after [MAX_REVERSIBLE_MOVES], the draw has to be claimed and otherwise play continues.
+/// Keep the counter of `board` below the limit so it is not considered done locally.
+fn without_move_limit(board: ChessBoard) -> ChessBoard {
+    if board.reversible_moves() >= MAX_REVERSIBLE_MOVES {
+        ChessBoard::new(*board.inner(), MAX_REVERSIBLE_MOVES - 1, board.full_moves())
+    } else {
+        board
+    }
+}
+
+/// Call `f` for each json line of the stream at `url`, until the stream ends or `f` returns `false`.
+fn for_each_line(
+    agent: &ureq::Agent,
+    token: &str,
+    url: &str,
+    mut f: impl FnMut(&Value) -> Result<bool, String>,
+) -> Result<(), String> {
+    let response = agent
+        .get(url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .call()
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    for line in BufReader::new(response.into_reader()).lines() {
+        let line = line.map_err(|e| format!("Reading from {} failed: {}", url, e))?;
+        // empty lines are sent to keep the connection alive
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(&line).map_err(|e| format!("Invalid json '{}': {}", line, e))?;
+        if !f(&value)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn get_json(agent: &ureq::Agent, token: &str, url: &str) -> Result<Value, String> {
+    let response = agent
+        .get(url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .call()
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    let body = response
+        .into_string()
+        .map_err(|e| format!("Reading from {} failed: {}", url, e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Invalid json '{}': {}", body, e))
+}
+
+fn post(agent: &ureq::Agent, token: &str, url: &str) -> Result<(), String> {
+    agent
+        .post(url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .call()
+        .map(|_| ())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))
+}
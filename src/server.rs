@@ -0,0 +1,173 @@
+//! A simple line-delimited JSON protocol to play games against a bot, so frontends and scripts
+//! in any language can use the bots in this crate.
+//!
+//! Each request is a single line containing an object `{"id": ..., "method": ..., "params": {...}}`,
+//! where `id` and `params` are optional. Each request gets a single line response containing either
+//! `{"id": ..., "result": ...}` or `{"id": ..., "error": "message"}`.
+//!
+//! The supported methods are:
+//! * `new_game`: reset the board to the start position, returns the state.
+//! * `state`: returns the state.
+//! * `legal_moves`: returns the list of available moves.
+//! * `play` with param `move`: play the given move, returns the state.
+//! * `bot_move`: let the bot pick and play a move, returns `{"move": ..., "state": ...}`.
+//!
+//! The state is an object `{"fen": ..., "next_player": "A" | "B", "outcome": null | "A" | "B" | "draw", "moves": [...]}`.
+//! Moves and positions are written in the notation of [ExternalBoard].
+use std::io::{BufRead, BufReader, Read, Write};
+
+use internal_iterator::InternalIterator;
+use serde_json::{json, Value};
+
+use crate::ai::external::ExternalBoard;
+use crate::ai::Bot;
+use crate::board::{Outcome, Player};
+
+#[derive(Debug)]
+pub struct GameServer<B: ExternalBoard, T: Bot<B>> {
+    start: B,
+    board: B,
+    moves: Vec<B::Move>,
+    bot: T,
+}
+
+impl<B: ExternalBoard, T: Bot<B>> GameServer<B, T> {
+    pub fn new(start: B, bot: T) -> Self {
+        GameServer {
+            board: start.clone(),
+            start,
+            moves: vec![],
+            bot,
+        }
+    }
+
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// Handle a single request line and return the response line.
+    pub fn handle(&mut self, request: &str) -> String {
+        let (id, result) = match serde_json::from_str::<Value>(request) {
+            Ok(request) => {
+                let id = request.get("id").cloned().unwrap_or(Value::Null);
+                (id, self.handle_request(&request))
+            }
+            Err(e) => (Value::Null, Err(format!("Invalid json: {}", e))),
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "id": id, "result": result }),
+            Err(error) => json!({ "id": id, "error": error }),
+        };
+        response.to_string()
+    }
+
+    fn handle_request(&mut self, request: &Value) -> Result<Value, String> {
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "Missing method".to_string())?;
+
+        match method {
+            "new_game" => {
+                self.board = self.start.clone();
+                self.moves.clear();
+                Ok(self.state())
+            }
+            "state" => Ok(self.state()),
+            "legal_moves" => {
+                if self.board.is_done() {
+                    return Ok(json!([]));
+                }
+                let moves: Vec<String> = self
+                    .board
+                    .available_moves()
+                    .map(|mv| self.board.external_format_move(mv))
+                    .collect();
+                Ok(json!(moves))
+            }
+            "play" => {
+                let mv_str = request
+                    .get("params")
+                    .and_then(|params| params.get("move"))
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| "Missing param 'move'".to_string())?;
+
+                if self.board.is_done() {
+                    return Err("The game is already done".to_string());
+                }
+                let mv = self
+                    .board
+                    .external_parse_move(mv_str)
+                    .filter(|&mv| self.board.is_available_move(mv))
+                    .ok_or_else(|| format!("Move '{}' is not available", mv_str))?;
+
+                self.play(mv);
+                Ok(self.state())
+            }
+            "bot_move" => {
+                if self.board.is_done() {
+                    return Err("The game is already done".to_string());
+                }
+                let mv = self.bot.select_move(&self.board);
+                let mv_str = self.board.external_format_move(mv);
+
+                self.play(mv);
+                Ok(json!({ "move": mv_str, "state": self.state() }))
+            }
+            _ => Err(format!("Unknown method '{}'", method)),
+        }
+    }
+
+    fn play(&mut self, mv: B::Move) {
+        self.board.play(mv);
+        self.moves.push(mv);
+    }
+
+    fn state(&self) -> Value {
+        let mut board = self.start.clone();
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|&mv| {
+                let mv_str = board.external_format_move(mv);
+                board.play(mv);
+                mv_str
+            })
+            .collect();
+
+        let outcome = match self.board.outcome() {
+            None => Value::Null,
+            Some(Outcome::WonBy(player)) => json!(player_str(player)),
+            Some(Outcome::Draw) => json!("draw"),
+        };
+
+        json!({
+            "fen": self.board.external_fen(),
+            "next_player": player_str(self.board.next_player()),
+            "outcome": outcome,
+            "moves": moves,
+        })
+    }
+
+    /// Handle requests from `input` until it is closed, writing the responses to `output`.
+    pub fn run(&mut self, input: impl Read, mut output: impl Write) -> std::io::Result<()> {
+        for line in BufReader::new(input).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            writeln!(output, "{}", self.handle(&line))?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn player_str(player: Player) -> &'static str {
+    match player {
+        Player::A => "A",
+        Player::B => "B",
+    }
+}
@@ -1,4 +1,6 @@
 //! The Universal Ataxx Interface (UAI) is a derivative of the UCI
 //! protocol used for chess programs.
+#[cfg(feature = "native")]
 pub mod client;
+#[cfg(feature = "native")]
 mod command;
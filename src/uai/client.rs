@@ -1,11 +1,17 @@
 use std::io::Write;
 use std::io::{BufRead, BufReader, BufWriter, Read};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use crate::ai::Bot;
 use crate::board::{Board, Player};
 use crate::games::ataxx::{AtaxxBoard, Move};
 use crate::uai::command::{Command, GoTimeSettings, Position};
+use crate::util::bot_game::TimeControl;
 
+/// Run a UAI engine on the given input and output streams until `quit` is received or the input is closed.
+///
+/// `bot` is called with the current board and the time in milliseconds it should use,
+/// and returns the selected move together with some info that is written to `log`.
 pub fn run(
     mut bot: impl FnMut(&AtaxxBoard, u32) -> (Move, String),
     name: &str,
@@ -30,10 +36,15 @@ pub fn run(
         output.flush()?;
 
         line.clear();
-        input.read_line(&mut line)?;
+        if input.read_line(&mut line)? == 0 {
+            // the input was closed
+            return Ok(());
+        }
         let line = line.trim();
-        writeln!(log, "> {}", line).unwrap();
-        println!("> {}", line);
+        if line.is_empty() {
+            continue;
+        }
+        writeln!(log, "> {}", line)?;
 
         let command = Command::parse(line).unwrap_or_else(|_| panic!("Failed to parse command '{}'", line));
 
@@ -56,25 +67,38 @@ pub fn run(
             Command::NewGame => {
                 curr_board = Some(AtaxxBoard::default());
             }
-            Command::Position(position) => {
-                curr_board = Some(match position {
+            Command::Position { position, moves } => {
+                let mut board = match position {
                     Position::StartPos => AtaxxBoard::default(),
                     Position::Fen(fen) => AtaxxBoard::from_fen(fen),
-                });
+                };
+                for mv in moves.into_iter().flat_map(|moves| moves.split_whitespace()) {
+                    let mv: Move = mv.parse().unwrap_or_else(|e| panic!("{}", e));
+                    board.play(mv);
+                }
+                curr_board = Some(board);
             }
             Command::Go(time_settings) => {
                 let curr_board = curr_board.as_ref().expect("Received go command without having a board");
 
-                let time_to_use = match time_settings {
-                    GoTimeSettings::Move(time) => time * 95 / 100,
-                    GoTimeSettings::Clock { w_time, b_time, .. } => {
-                        let time_left = match curr_board.next_player() {
-                            Player::A => w_time,
-                            Player::B => b_time,
+                let ms = |time: u32| Duration::from_millis(time as u64);
+                let budget = match time_settings {
+                    GoTimeSettings::Move(time) => TimeControl::per_move(ms(time)).budget(None),
+                    GoTimeSettings::Clock {
+                        w_time,
+                        b_time,
+                        w_inc,
+                        b_inc,
+                    } => {
+                        let (time_left, inc) = match curr_board.next_player() {
+                            Player::A => (w_time, w_inc),
+                            Player::B => (b_time, b_inc),
                         };
-                        time_left / 30
+                        TimeControl::per_game(ms(time_left), ms(inc)).budget(Some(ms(time_left)))
                     }
                 };
+                // both time settings impose a limit, so there always is a budget
+                let time_to_use = (budget.unwrap().as_secs_f64() * 1000.0).round() as u32;
 
                 writeln!(log, "time_to_use: {}", time_to_use)?;
 
@@ -82,12 +106,39 @@ pub fn run(
                 let (best_move, info) = bot(curr_board, time_to_use);
                 let time_used = (Instant::now() - start).as_secs_f32();
 
-                writeln!(log, "best_move: {:?}, time_used: {}, {}", best_move, time_used, info)?;
-                writeln!(output, "bestmove {}", best_move.to_uai())?;
+                writeln!(log, "best_move: {}, time_used: {}, {}", best_move, time_used, info)?;
+                writeln!(output, "bestmove {}", best_move)?;
             }
             Command::Quit => {
-                //nothing to do here
+                output.flush()?;
+                log.flush()?;
+                return Ok(());
             }
         }
     }
 }
+
+/// Run a UAI engine for `bot`, see [run].
+///
+/// The time settings sent by the GUI are turned into a budget for each move the same way as
+/// [TimeControl::budget], which is passed to [select_move_timed](Bot::select_move_timed).
+pub fn run_bot(
+    mut bot: impl Bot<AtaxxBoard>,
+    name: &str,
+    author: &str,
+    input: impl Read,
+    output: impl Write,
+    log: impl Write,
+) -> std::io::Result<()> {
+    run(
+        |board, time| {
+            let (mv, value) = bot.select_move_timed(board, Duration::from_millis(time as u64));
+            (mv, format!("value: {:?}", value))
+        },
+        name,
+        author,
+        input,
+        output,
+        log,
+    )
+}
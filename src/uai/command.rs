@@ -4,9 +4,15 @@ pub enum Command<'a> {
     IsReady,
     NewGame,
     Quit,
-    Position(Position<'a>),
+    Position {
+        position: Position<'a>,
+        moves: Option<&'a str>,
+    },
     Go(GoTimeSettings),
-    SetOption { name: &'a str, value: &'a str },
+    SetOption {
+        name: &'a str,
+        value: &'a str,
+    },
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -39,8 +45,9 @@ mod parse {
     use nom::branch::alt;
     use nom::bytes::complete::{tag, take_until, take_while};
     use nom::character::complete::digit1;
-    use nom::combinator::{eof, map, value};
-    use nom::sequence::{preceded, terminated, tuple};
+    use nom::combinator::{eof, map, opt, value};
+    use nom::multi::separated_list1;
+    use nom::sequence::{preceded, separated_pair, terminated, tuple};
     use nom::IResult;
 
     use crate::uai::command::{Command, GoTimeSettings, Position};
@@ -50,22 +57,24 @@ mod parse {
 
         let move_time = preceded(tag("movetime "), map(int(), GoTimeSettings::Move));
 
+        // the clock values can be given in any order, and the increments are optional
         let clock_time = map(
-            tuple((
-                tag("btime "),
-                int(),
-                tag(" wtime "),
-                int(),
-                tag(" binc "),
-                int(),
-                tag(" winc "),
-                int(),
-            )),
-            |(_, b_time, _, w_time, _, b_inc, _, w_inc)| GoTimeSettings::Clock {
-                b_time,
-                w_time,
-                b_inc,
-                w_inc,
+            separated_list1(
+                tag(" "),
+                separated_pair(
+                    alt((tag("btime"), tag("wtime"), tag("binc"), tag("winc"))),
+                    tag(" "),
+                    int(),
+                ),
+            ),
+            |pairs: Vec<(&str, u32)>| {
+                let get = |key: &str| pairs.iter().find(|&&(k, _)| k == key).map_or(0, |&(_, v)| v);
+                GoTimeSettings::Clock {
+                    b_time: get("btime"),
+                    w_time: get("wtime"),
+                    b_inc: get("binc"),
+                    w_inc: get("winc"),
+                }
             },
         );
 
@@ -74,11 +83,17 @@ mod parse {
         let position = preceded(
             tag("position "),
             map(
-                alt((
-                    value(Position::StartPos, tag("startpos")),
-                    preceded(tag("fen "), map(take_while(|_| true), Position::Fen)),
+                tuple((
+                    alt((
+                        value(Position::StartPos, tag("startpos")),
+                        preceded(
+                            tag("fen "),
+                            map(alt((take_until(" moves "), take_while(|_| true))), Position::Fen),
+                        ),
+                    )),
+                    opt(preceded(tag(" moves "), take_while(|_| true))),
                 )),
-                Command::Position,
+                |(position, moves)| Command::Position { position, moves },
             ),
         );
 
@@ -117,4 +132,62 @@ mod tests {
         assert_eq!(Ok(Command::NewGame), Command::parse("uainewgame"));
         assert_eq!(Ok(Command::Quit), Command::parse("quit"));
     }
+
+    #[test]
+    fn position() {
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::StartPos,
+                moves: None
+            }),
+            Command::parse("position startpos")
+        );
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::StartPos,
+                moves: Some("g2 a1a3")
+            }),
+            Command::parse("position startpos moves g2 a1a3")
+        );
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::Fen("x5o/7/7/7/7/7/o5x x 0 1"),
+                moves: None
+            }),
+            Command::parse("position fen x5o/7/7/7/7/7/o5x x 0 1")
+        );
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::Fen("x5o/7/7/7/7/7/o5x x 0 1"),
+                moves: Some("b6")
+            }),
+            Command::parse("position fen x5o/7/7/7/7/7/o5x x 0 1 moves b6")
+        );
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(
+            Ok(Command::Go(GoTimeSettings::Move(1000))),
+            Command::parse("go movetime 1000")
+        );
+        assert_eq!(
+            Ok(Command::Go(GoTimeSettings::Clock {
+                b_time: 100,
+                w_time: 200,
+                b_inc: 10,
+                w_inc: 20
+            })),
+            Command::parse("go btime 100 wtime 200 binc 10 winc 20")
+        );
+        assert_eq!(
+            Ok(Command::Go(GoTimeSettings::Clock {
+                b_time: 100,
+                w_time: 200,
+                b_inc: 0,
+                w_inc: 0
+            })),
+            Command::parse("go wtime 200 btime 100")
+        );
+    }
 }
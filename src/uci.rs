@@ -0,0 +1,8 @@
+//! Generic scaffolding for the Universal Chess Interface (UCI) protocol.
+//!
+//! The game-specific details (start position, FEN parsing and move notation) are supplied
+//! by implementing [ExternalBoard](crate::ai::external::ExternalBoard), the search itself by implementing
+//! [UciSearcher](crate::uci::engine::UciSearcher).
+pub mod command;
+pub mod engine;
+pub mod options;
@@ -0,0 +1,240 @@
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Command<'a> {
+    Uci,
+    Debug(bool),
+    IsReady,
+    SetOption {
+        name: &'a str,
+        value: Option<&'a str>,
+    },
+    NewGame,
+    Position {
+        position: Position<'a>,
+        moves: Option<&'a str>,
+    },
+    Go(GoSettings),
+    Stop,
+    PonderHit,
+    Quit,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Position<'a> {
+    StartPos,
+    Fen(&'a str),
+}
+
+/// The limits sent with a `go` command. Fields that were not present are `None` or `false`.
+/// Times are in milliseconds.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct GoSettings {
+    pub w_time: Option<u32>,
+    pub b_time: Option<u32>,
+    pub w_inc: Option<u32>,
+    pub b_inc: Option<u32>,
+    pub moves_to_go: Option<u32>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub move_time: Option<u32>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl<'a> Command<'a> {
+    pub fn parse(input: &'a str) -> Result<Command<'a>, nom::Err<nom::error::Error<&'a str>>> {
+        parse::command(input).map(|(left, command)| {
+            assert!(left.is_empty());
+            command
+        })
+    }
+}
+
+mod parse {
+    use nom::branch::alt;
+    use nom::bytes::complete::{tag, take_until, take_while};
+    use nom::character::complete::digit1;
+    use nom::combinator::{eof, map, opt, value};
+    use nom::multi::separated_list0;
+    use nom::sequence::{preceded, separated_pair, terminated, tuple};
+    use nom::IResult;
+
+    use crate::uci::command::{Command, GoSettings, Position};
+
+    #[derive(Debug, Copy, Clone)]
+    enum GoArg {
+        WTime(u32),
+        BTime(u32),
+        WInc(u32),
+        BInc(u32),
+        MovesToGo(u32),
+        Depth(u32),
+        Nodes(u64),
+        MoveTime(u32),
+        Infinite,
+        Ponder,
+    }
+
+    pub fn command(input: &str) -> IResult<&str, Command<'_>> {
+        let int = || map(digit1, |s: &str| s.parse::<u32>().unwrap());
+
+        let go_arg = alt((
+            map(preceded(tag("wtime "), int()), GoArg::WTime),
+            map(preceded(tag("btime "), int()), GoArg::BTime),
+            map(preceded(tag("winc "), int()), GoArg::WInc),
+            map(preceded(tag("binc "), int()), GoArg::BInc),
+            map(preceded(tag("movestogo "), int()), GoArg::MovesToGo),
+            map(preceded(tag("depth "), int()), GoArg::Depth),
+            map(preceded(tag("nodes "), digit1), |s: &str| {
+                GoArg::Nodes(s.parse().unwrap())
+            }),
+            map(preceded(tag("movetime "), int()), GoArg::MoveTime),
+            value(GoArg::Infinite, tag("infinite")),
+            value(GoArg::Ponder, tag("ponder")),
+        ));
+
+        let go = preceded(
+            tag("go"),
+            map(opt(preceded(tag(" "), separated_list0(tag(" "), go_arg))), |args| {
+                let mut settings = GoSettings::default();
+                for arg in args.unwrap_or_default() {
+                    match arg {
+                        GoArg::WTime(t) => settings.w_time = Some(t),
+                        GoArg::BTime(t) => settings.b_time = Some(t),
+                        GoArg::WInc(t) => settings.w_inc = Some(t),
+                        GoArg::BInc(t) => settings.b_inc = Some(t),
+                        GoArg::MovesToGo(n) => settings.moves_to_go = Some(n),
+                        GoArg::Depth(d) => settings.depth = Some(d),
+                        GoArg::Nodes(n) => settings.nodes = Some(n),
+                        GoArg::MoveTime(t) => settings.move_time = Some(t),
+                        GoArg::Infinite => settings.infinite = true,
+                        GoArg::Ponder => settings.ponder = true,
+                    }
+                }
+                Command::Go(settings)
+            }),
+        );
+
+        let position = preceded(
+            tag("position "),
+            map(
+                tuple((
+                    alt((
+                        value(Position::StartPos, tag("startpos")),
+                        preceded(
+                            tag("fen "),
+                            map(alt((take_until(" moves "), take_while(|_| true))), Position::Fen),
+                        ),
+                    )),
+                    opt(preceded(tag(" moves "), take_while(|_| true))),
+                )),
+                |(position, moves)| Command::Position { position, moves },
+            ),
+        );
+
+        let set_option = preceded(
+            tag("setoption name "),
+            alt((
+                map(
+                    separated_pair(take_until(" value "), tag(" value "), take_while(|_| true)),
+                    |(name, value)| Command::SetOption {
+                        name,
+                        value: Some(value),
+                    },
+                ),
+                map(take_while(|_| true), |name| Command::SetOption { name, value: None }),
+            )),
+        );
+
+        let debug = preceded(
+            tag("debug "),
+            map(alt((value(true, tag("on")), value(false, tag("off")))), Command::Debug),
+        );
+
+        let main = alt((
+            value(Command::NewGame, tag("ucinewgame")),
+            value(Command::Uci, tag("uci")),
+            value(Command::IsReady, tag("isready")),
+            value(Command::Stop, tag("stop")),
+            value(Command::PonderHit, tag("ponderhit")),
+            value(Command::Quit, tag("quit")),
+            debug,
+            position,
+            go,
+            set_option,
+        ));
+
+        let mut complete = terminated(main, eof);
+
+        complete(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        assert_eq!(Ok(Command::Uci), Command::parse("uci"));
+        assert_eq!(Ok(Command::IsReady), Command::parse("isready"));
+        assert_eq!(Ok(Command::NewGame), Command::parse("ucinewgame"));
+        assert_eq!(Ok(Command::Stop), Command::parse("stop"));
+        assert_eq!(Ok(Command::PonderHit), Command::parse("ponderhit"));
+        assert_eq!(Ok(Command::Quit), Command::parse("quit"));
+        assert_eq!(Ok(Command::Debug(true)), Command::parse("debug on"));
+    }
+
+    #[test]
+    fn set_option() {
+        assert_eq!(
+            Ok(Command::SetOption {
+                name: "Hash",
+                value: Some("128")
+            }),
+            Command::parse("setoption name Hash value 128")
+        );
+        assert_eq!(
+            Ok(Command::SetOption {
+                name: "Clear Hash",
+                value: None
+            }),
+            Command::parse("setoption name Clear Hash")
+        );
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(Ok(Command::Go(GoSettings::default())), Command::parse("go"));
+
+        let expected = GoSettings {
+            w_time: Some(1000),
+            b_time: Some(2000),
+            w_inc: Some(10),
+            moves_to_go: Some(20),
+            ponder: true,
+            ..GoSettings::default()
+        };
+        assert_eq!(
+            Ok(Command::Go(expected)),
+            Command::parse("go ponder wtime 1000 btime 2000 winc 10 movestogo 20")
+        );
+        assert_eq!(
+            Ok(Command::Go(GoSettings {
+                infinite: true,
+                ..GoSettings::default()
+            })),
+            Command::parse("go infinite")
+        );
+    }
+
+    #[test]
+    fn position() {
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::Fen("8/8/8/8/8/8/8/K1k5 w - - 0 1"),
+                moves: Some("a1a2 c1c2"),
+            }),
+            Command::parse("position fen 8/8/8/8/8/8/8/K1k5 w - - 0 1 moves a1a2 c1c2")
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! The UCI engine loop. The search runs on a separate thread so `stop` and `ponderhit` can be handled while searching.
+//! The loop itself requires the `native` feature.
+use std::fmt::{Debug, Formatter};
+#[cfg(feature = "native")]
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "native")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "native")]
+use std::thread::JoinHandle;
+#[cfg(feature = "native")]
+use std::time::Duration;
+
+use crate::ai::external::ExternalBoard;
+use crate::ai::Bot;
+use crate::uci::command::GoSettings;
+#[cfg(feature = "native")]
+use crate::uci::command::{Command, Position};
+use crate::uci::options::Options;
+#[cfg(feature = "native")]
+use crate::uci::options::UciOption;
+
+/// The flags through which the engine loop controls a running search.
+#[derive(Debug, Default)]
+pub struct SearchControl {
+    stop: AtomicBool,
+    pondering: AtomicBool,
+}
+
+impl SearchControl {
+    /// Whether the search should return as soon as possible.
+    pub fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Whether the search is pondering, during which the time limits should not be applied yet.
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+}
+
+pub trait UciSearcher<B: ExternalBoard>: Send + 'static {
+    /// Search `board` within the limits given by `settings` and `options`.
+    /// Returns the best move and optionally the expected reply, which the GUI can ponder on.
+    ///
+    /// Implementations should regularly check `control` and return as soon as possible once it says to stop.
+    fn search(
+        &mut self,
+        board: &B,
+        settings: &GoSettings,
+        options: &Options,
+        control: &SearchControl,
+    ) -> (B::Move, Option<B::Move>);
+
+    /// Called when the GUI starts a new game, can be used to clear caches.
+    fn new_game(&mut self) {}
+}
+
+/// Adapter to use any [Bot] as a [UciSearcher]. The limits and `stop` commands are ignored.
+pub struct BotSearcher<T>(pub T);
+
+impl<T: Debug> Debug for BotSearcher<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BotSearcher({:?})", self.0)
+    }
+}
+
+impl<B: ExternalBoard, T: Bot<B> + Send + 'static> UciSearcher<B> for BotSearcher<T> {
+    fn search(&mut self, board: &B, _: &GoSettings, _: &Options, _: &SearchControl) -> (B::Move, Option<B::Move>) {
+        (self.0.select_move(board), None)
+    }
+}
+
+#[cfg(feature = "native")]
+struct RunningSearch<S> {
+    handle: JoinHandle<S>,
+    control: Arc<SearchControl>,
+}
+
+#[cfg(feature = "native")]
+/// Run a UCI engine on the given input and output streams until `quit` is received or the input is closed.
+pub fn run<B: ExternalBoard, S: UciSearcher<B>>(
+    searcher: S,
+    name: &str,
+    author: &str,
+    options: Vec<UciOption>,
+    input: impl Read,
+    output: impl Write + Send + 'static,
+) -> std::io::Result<()> {
+    let input = &mut BufReader::new(input);
+    let output = Arc::new(Mutex::new(output));
+
+    let mut options = Options::new(options);
+    let mut searcher = Some(searcher);
+    let mut running: Option<RunningSearch<S>> = None;
+    let mut board = B::external_start_pos();
+
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            // the input was closed
+            finish_search(&mut running, &mut searcher);
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(_) => {
+                // the protocol says to ignore unknown commands
+                send(&output, &format!("info string ignoring unknown command '{}'", line))?;
+                continue;
+            }
+        };
+
+        match command {
+            Command::Uci => {
+                send(&output, &format!("id name {}", name))?;
+                send(&output, &format!("id author {}", author))?;
+                for option in options.options() {
+                    send(&output, &option.to_string())?;
+                }
+                send(&output, "uciok")?;
+            }
+            Command::Debug(_) => {}
+            Command::IsReady => {
+                send(&output, "readyok")?;
+            }
+            Command::SetOption { name, value } => {
+                if let Err(e) = options.set(name, value) {
+                    send(&output, &format!("info string {}", e))?;
+                }
+            }
+            Command::NewGame => {
+                finish_search(&mut running, &mut searcher);
+                searcher.as_mut().unwrap().new_game();
+                board = B::external_start_pos();
+            }
+            Command::Position { position, moves } => match parse_position(position, moves) {
+                Ok(new_board) => board = new_board,
+                Err(e) => send(&output, &format!("info string {}", e))?,
+            },
+            Command::Go(settings) => {
+                finish_search(&mut running, &mut searcher);
+                if board.is_done() {
+                    send(&output, "info string cannot search a finished game")?;
+                    continue;
+                }
+                running = Some(start_search(
+                    searcher.take().unwrap(),
+                    board.clone(),
+                    settings,
+                    options.clone(),
+                    Arc::clone(&output),
+                ));
+            }
+            Command::Stop => {
+                finish_search(&mut running, &mut searcher);
+            }
+            Command::PonderHit => {
+                if let Some(running) = &running {
+                    running.control.pondering.store(false, Ordering::Relaxed);
+                }
+            }
+            Command::Quit => {
+                finish_search(&mut running, &mut searcher);
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+fn parse_position<B: ExternalBoard>(position: Position, moves: Option<&str>) -> Result<B, String> {
+    let mut board = match position {
+        Position::StartPos => B::external_start_pos(),
+        Position::Fen(fen) => B::external_from_fen(fen).ok_or_else(|| format!("invalid fen '{}'", fen))?,
+    };
+
+    for mv_str in moves.into_iter().flat_map(|moves| moves.split_whitespace()) {
+        let mv = board
+            .external_parse_move(mv_str)
+            .filter(|&mv| !board.is_done() && board.is_available_move(mv))
+            .ok_or_else(|| format!("invalid move '{}'", mv_str))?;
+        board.play(mv);
+    }
+
+    Ok(board)
+}
+
+#[cfg(feature = "native")]
+fn start_search<B: ExternalBoard, S: UciSearcher<B>>(
+    mut searcher: S,
+    board: B,
+    settings: GoSettings,
+    options: Options,
+    output: Arc<Mutex<impl Write + Send + 'static>>,
+) -> RunningSearch<S> {
+    let control = Arc::new(SearchControl {
+        stop: AtomicBool::new(false),
+        pondering: AtomicBool::new(settings.ponder),
+    });
+    let thread_control = Arc::clone(&control);
+
+    let handle = std::thread::spawn(move || {
+        let (best_move, ponder_move) = searcher.search(&board, &settings, &options, &thread_control);
+
+        // the protocol does not allow sending bestmove before the GUI has ended an infinite or ponder search
+        while (settings.infinite || thread_control.is_pondering()) && !thread_control.should_stop() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut line = format!("bestmove {}", board.external_format_move(best_move));
+        if let Some(ponder_move) = ponder_move {
+            let next_board = board.clone_and_play(best_move);
+            line.push_str(&format!(" ponder {}", next_board.external_format_move(ponder_move)));
+        }
+        send(&output, &line).unwrap();
+
+        searcher
+    });
+
+    RunningSearch { handle, control }
+}
+
+#[cfg(feature = "native")]
+/// Stop the running search (if any) and wait for it to finish, putting the searcher back in `searcher`.
+fn finish_search<S>(running: &mut Option<RunningSearch<S>>, searcher: &mut Option<S>) {
+    if let Some(running) = running.take() {
+        running.control.stop.store(true, Ordering::Relaxed);
+        *searcher = Some(running.handle.join().expect("Search thread panicked"));
+    }
+}
+
+#[cfg(feature = "native")]
+fn send(output: &Mutex<impl Write>, line: &str) -> std::io::Result<()> {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
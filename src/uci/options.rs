@@ -0,0 +1,124 @@
+//! A registry of the options an engine exposes with `option name ... type ...`.
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The type and default value of a single UCI option.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OptionKind {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, vars: Vec<String> },
+    Button,
+    String { default: String },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UciOption {
+    pub name: String,
+    pub kind: OptionKind,
+}
+
+/// The current values of all registered options.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    options: Vec<UciOption>,
+    values: HashMap<String, String>,
+}
+
+impl UciOption {
+    pub fn new(name: &str, kind: OptionKind) -> Self {
+        UciOption {
+            name: name.to_string(),
+            kind,
+        }
+    }
+
+    fn default_value(&self) -> Option<String> {
+        match &self.kind {
+            OptionKind::Check { default } => Some(default.to_string()),
+            OptionKind::Spin { default, .. } => Some(default.to_string()),
+            OptionKind::Combo { default, .. } => Some(default.clone()),
+            OptionKind::Button => None,
+            OptionKind::String { default } => Some(default.clone()),
+        }
+    }
+
+    fn is_valid(&self, value: Option<&str>) -> bool {
+        match (&self.kind, value) {
+            (OptionKind::Button, None) => true,
+            (OptionKind::Check { .. }, Some(value)) => value == "true" || value == "false",
+            (OptionKind::Spin { min, max, .. }, Some(value)) => {
+                matches!(value.parse::<i64>(), Ok(v) if (*min..=*max).contains(&v))
+            }
+            (OptionKind::Combo { vars, .. }, Some(value)) => vars.iter().any(|v| v == value),
+            (OptionKind::String { .. }, Some(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for UciOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.kind {
+            OptionKind::Check { default } => write!(f, "check default {}", default),
+            OptionKind::Spin { default, min, max } => write!(f, "spin default {} min {} max {}", default, min, max),
+            OptionKind::Combo { default, vars } => {
+                write!(f, "combo default {}", default)?;
+                for var in vars {
+                    write!(f, " var {}", var)?;
+                }
+                Ok(())
+            }
+            OptionKind::Button => write!(f, "button"),
+            OptionKind::String { default } => write!(f, "string default {}", default),
+        }
+    }
+}
+
+impl Options {
+    pub fn new(options: Vec<UciOption>) -> Self {
+        let values = options
+            .iter()
+            .filter_map(|option| option.default_value().map(|value| (option.name.clone(), value)))
+            .collect();
+        Options { options, values }
+    }
+
+    pub fn options(&self) -> &[UciOption] {
+        &self.options
+    }
+
+    /// Set the option `name` to `value`.
+    /// Returns an error message if the option does not exist or the value is invalid for the option.
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<(), String> {
+        // option names are case insensitive
+        let option = self
+            .options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown option '{}'", name))?;
+
+        if !option.is_valid(value) {
+            return Err(format!("invalid value {:?} for option '{}'", value, option.name));
+        }
+
+        if let Some(value) = value {
+            self.values.insert(option.name.clone(), value.to_string());
+        }
+        Ok(())
+    }
+
+    /// Get the current value of the option `name`, or `None` if the option does not exist.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|s| s.as_str())
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get(name).and_then(|s| s.parse().ok())
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(|s| s.parse().ok())
+    }
+}
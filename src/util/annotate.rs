@@ -0,0 +1,88 @@
+//! Analyse recorded games with a bot, for post-mortem analysis of surprising results.
+//!
+//! The annotated game is a [Replay] again, so it can be stored as is, or converted to PGN comments with
+//! [to_pgn](Replay::to_pgn) or to JSON with [to_json](Replay::to_json).
+use rand::Rng;
+
+use crate::ai::external::ExternalBoard;
+use crate::ai::mcts::MCTSBot;
+use crate::util::replay::{Replay, ReplayMove};
+use crate::wdl::POV;
+
+/// A move is flagged as a blunder if it loses at least this much value compared to the best move.
+/// Values range from `-1` (loss) to `1` (win).
+pub const BLUNDER_THRESHOLD: f32 = 0.5;
+
+/// The number of best moves listed for each position.
+const BEST_MOVE_COUNT: usize = 3;
+
+/// Replay `game` and evaluate every position with `bot` using `visits` iterations.
+///
+/// Each move gets the following annotations, in addition to the ones it already had:
+/// * `eval`: the value of the position after the move, from the POV of the player that made the move.
+/// * `best_eval`: the value of the position before the move, which is the value of the best move.
+/// * `best`: the best moves according to the bot, best first and separated by commas.
+/// * `blunder=1`, only if the move lost more than [BLUNDER_THRESHOLD] compared to the best move.
+pub fn annotate<B: ExternalBoard, R: Rng>(game: &Replay<B>, bot: &mut MCTSBot<R>, visits: u64) -> Replay<B> {
+    let boards = game.boards();
+
+    // the value of each position from the POV of the next player, and the best moves in it
+    let evals: Vec<(f32, Vec<B::Move>)> = boards
+        .iter()
+        .map(|board| match board.outcome() {
+            Some(outcome) => (outcome.pov(board.next_player()).sign::<f32>(), vec![]),
+            None => {
+                let tree = bot.build_tree_with_iterations(board, visits);
+                let best_child = tree.best_child();
+
+                let mut children: Vec<usize> = tree[0].children.unwrap().iter().collect();
+                children.sort_by_key(|&c| (c != best_child, -tree[c].visits));
+                let best_moves = children
+                    .iter()
+                    .take(BEST_MOVE_COUNT)
+                    .map(|&c| tree[c].last_move.unwrap())
+                    .collect();
+
+                (tree.wdl().value(), best_moves)
+            }
+        })
+        .collect();
+
+    let mut result = Replay::new(game.start.clone());
+    for (i, mv) in game.moves.iter().enumerate() {
+        let board = &boards[i];
+        let next = &boards[i + 1];
+
+        let (best_eval, best_moves) = &evals[i];
+        let eval = if next.next_player() == board.next_player() {
+            evals[i + 1].0
+        } else {
+            -evals[i + 1].0
+        };
+
+        let best_str: Vec<String> = best_moves.iter().map(|&m| board.external_format_move(m)).collect();
+        let mut annotations = vec![
+            ("eval".to_string(), format!("{:.3}", eval)),
+            ("best_eval".to_string(), format!("{:.3}", best_eval)),
+            ("best".to_string(), best_str.join(",")),
+        ];
+        if best_eval - eval > BLUNDER_THRESHOLD {
+            annotations.push(("blunder".to_string(), "1".to_string()));
+        }
+
+        let mut all_annotations: Vec<(String, String)> = mv
+            .annotations
+            .iter()
+            .filter(|(key, _)| !annotations.iter().any(|(k, _)| k == key))
+            .cloned()
+            .collect();
+        all_annotations.extend(annotations);
+
+        result.moves.push(ReplayMove {
+            mv: mv.mv,
+            annotations: all_annotations,
+        });
+    }
+
+    result
+}
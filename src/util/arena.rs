@@ -0,0 +1,132 @@
+//! An append-only vector that grows in chunks, used to store search trees.
+//!
+//! Growing a [Vec] copies all elements to a new allocation, which stalls a search for a noticeable time once a tree
+//! has tens of millions of nodes and temporarily needs twice the memory. An [Arena] never moves its elements,
+//! it only allocates a new chunk when the last one is full. Every chunk is twice as large as the previous one,
+//! so small trees stay small and large trees only need a few allocations.
+use std::cmp::min;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Index, IndexMut};
+
+/// The log2 of the number of elements in the first chunk.
+const FIRST_CHUNK_SHIFT: u32 = 8;
+const FIRST_CHUNK_SIZE: usize = 1 << FIRST_CHUNK_SHIFT;
+
+/// The chunk and the index within that chunk of the element at `index`.
+/// Chunk `k` holds `chunk_size(k)` elements and starts at index `chunk_size(k) - FIRST_CHUNK_SIZE`.
+fn locate(index: usize) -> (usize, usize) {
+    let shifted = index + FIRST_CHUNK_SIZE;
+    let chunk_shift = 63 - (shifted as u64).leading_zeros();
+    let chunk = (chunk_shift - FIRST_CHUNK_SHIFT) as usize;
+    (chunk, shifted - (1 << chunk_shift))
+}
+
+/// The number of elements chunk `chunk` holds once it's full.
+fn chunk_size(chunk: usize) -> usize {
+    FIRST_CHUNK_SIZE << chunk
+}
+
+/// An append-only vector with stable element addresses, indexed by `usize` like a [Vec].
+pub struct Arena<T> {
+    chunks: Vec<Vec<T>>,
+    len: usize,
+    max_len: Option<usize>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena {
+            chunks: vec![],
+            len: 0,
+            max_len: None,
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An arena that never allocates room for more than `max_len` elements, the last chunk is made smaller instead.
+    /// Pushing more elements still works, but then the elements of the last chunk can move.
+    pub fn with_max_len(max_len: usize) -> Self {
+        Arena {
+            max_len: Some(max_len),
+            ..Self::default()
+        }
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the allocated chunks can hold.
+    pub fn capacity(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.capacity()).sum()
+    }
+
+    /// Append `value` and return its index.
+    pub fn push(&mut self, value: T) -> usize {
+        let last_full = match self.chunks.last() {
+            None => true,
+            Some(last) => last.len() == chunk_size(self.chunks.len() - 1),
+        };
+        if last_full {
+            let mut size = chunk_size(self.chunks.len());
+            if let Some(max_len) = self.max_len {
+                size = min(size, max_len.saturating_sub(self.len));
+            }
+            self.chunks.push(Vec::with_capacity(size));
+        }
+        self.chunks.last_mut().unwrap().push(value);
+        self.len += 1;
+        self.len - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (chunk, offset) = locate(index);
+        self.chunks.get(chunk)?.get(offset)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let (chunk, offset) = locate(index);
+        self.chunks.get_mut(chunk)?.get_mut(offset)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.chunks.iter().flatten()
+    }
+}
+
+impl<T> Index<usize> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("Index {} out of bounds for length {}", index, len))
+    }
+}
+
+impl<T> IndexMut<usize> for Arena<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("Index {} out of bounds for length {}", index, len))
+    }
+}
+
+impl<T: Debug> Debug for Arena<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,355 @@
+//! Bitboards for rectangular boards of any size, with one bit per cell.
+//!
+//! Cell `(x, y)` is bit `y * W + x` of the underlying [Bits] type, which must have at least `W * H` bits:
+//! [u64] fits boards up to 8x8, [u128] up to 11x11 and [Wide] any size. Moving all cells one step in a direction is
+//! a shift combined with a mask that removes the cells that would wrap around to the other side of the board,
+//! so neighbor computations like the move generation of Ataxx-style games don't need any branches.
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not};
+
+/// An integer-like type that can be used as the storage of a [BitBoard].
+pub trait Bits:
+    Copy
+    + Eq
+    + Hash
+    + Debug
+    + Send
+    + Sync
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    /// The number of bits.
+    const BITS: u32;
+
+    fn zero() -> Self;
+
+    /// A value with only bit `index` set.
+    fn bit(index: u32) -> Self;
+
+    fn count_ones(self) -> u32;
+
+    /// The index of the lowest set bit, [BITS](Bits::BITS) if no bits are set.
+    fn trailing_zeros(self) -> u32;
+
+    /// Shift towards the more significant bits, bits that are shifted out are lost.
+    fn shift_up(self, n: u32) -> Self;
+
+    /// Shift towards the less significant bits, bits that are shifted out are lost.
+    fn shift_down(self, n: u32) -> Self;
+
+    fn has(self, index: u32) -> bool {
+        self & Self::bit(index) != Self::zero()
+    }
+}
+
+macro_rules! impl_bits_int {
+    ($t:ty) => {
+        impl Bits for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn zero() -> Self {
+                0
+            }
+
+            fn bit(index: u32) -> Self {
+                1 << index
+            }
+
+            fn count_ones(self) -> u32 {
+                <$t>::count_ones(self)
+            }
+
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+
+            fn shift_up(self, n: u32) -> Self {
+                self.checked_shl(n).unwrap_or(0)
+            }
+
+            fn shift_down(self, n: u32) -> Self {
+                self.checked_shr(n).unwrap_or(0)
+            }
+        }
+    };
+}
+
+impl_bits_int!(u64);
+impl_bits_int!(u128);
+
+/// Bits stored in `N` words of 64 bits, for boards that don't fit in a [u128]. Word `0` holds the lowest bits.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Wide<const N: usize>(pub [u64; N]);
+
+impl<const N: usize> Bits for Wide<N> {
+    const BITS: u32 = 64 * N as u32;
+
+    fn zero() -> Self {
+        Wide([0; N])
+    }
+
+    fn bit(index: u32) -> Self {
+        let mut words = [0; N];
+        words[(index / 64) as usize] = 1 << (index % 64);
+        Wide(words)
+    }
+
+    fn count_ones(self) -> u32 {
+        self.0.iter().map(|w| w.count_ones()).sum()
+    }
+
+    fn trailing_zeros(self) -> u32 {
+        match self.0.iter().position(|&w| w != 0) {
+            Some(i) => 64 * i as u32 + self.0[i].trailing_zeros(),
+            None => Self::BITS,
+        }
+    }
+
+    fn shift_up(self, n: u32) -> Self {
+        let (words, bits) = ((n / 64) as usize, n % 64);
+        let mut result = [0; N];
+        for (i, r) in result.iter_mut().enumerate().skip(words) {
+            let source = i - words;
+            *r = self.0[source] << bits;
+            if bits != 0 && source > 0 {
+                *r |= self.0[source - 1] >> (64 - bits);
+            }
+        }
+        Wide(result)
+    }
+
+    fn shift_down(self, n: u32) -> Self {
+        let (words, bits) = ((n / 64) as usize, n % 64);
+        let mut result = [0; N];
+        for (i, r) in result.iter_mut().enumerate().take(N.saturating_sub(words)) {
+            let source = i + words;
+            *r = self.0[source] >> bits;
+            if bits != 0 && source + 1 < N {
+                *r |= self.0[source + 1] << (64 - bits);
+            }
+        }
+        Wide(result)
+    }
+}
+
+macro_rules! impl_wide_op {
+    ($trait:ident, $fn:ident, $op:tt) => {
+        impl<const N: usize> $trait for Wide<N> {
+            type Output = Self;
+
+            fn $fn(self, rhs: Self) -> Self {
+                let mut result = self.0;
+                for (r, w) in result.iter_mut().zip(rhs.0.iter()) {
+                    *r $op *w;
+                }
+                Wide(result)
+            }
+        }
+    };
+}
+
+impl_wide_op!(BitAnd, bitand, &=);
+impl_wide_op!(BitOr, bitor, |=);
+impl_wide_op!(BitXor, bitxor, ^=);
+
+impl<const N: usize> Not for Wide<N> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        let mut result = self.0;
+        result.iter_mut().for_each(|w| *w = !*w);
+        Wide(result)
+    }
+}
+
+/// A set of cells of a `W` by `H` board, stored in `T`. Cells outside of the board are never set.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BitBoard<T: Bits, const W: u8, const H: u8>(T);
+
+impl<T: Bits, const W: u8, const H: u8> BitBoard<T, W, H> {
+    /// The number of cells.
+    pub const CELLS: u32 = W as u32 * H as u32;
+
+    pub fn empty() -> Self {
+        BitBoard(T::zero())
+    }
+
+    pub fn full() -> Self {
+        assert!(
+            Self::CELLS <= T::BITS,
+            "{}x{} cells don't fit in {} bits",
+            W,
+            H,
+            T::BITS
+        );
+        let mut bits = T::zero();
+        for i in 0..Self::CELLS {
+            bits = bits | T::bit(i);
+        }
+        BitBoard(bits)
+    }
+
+    /// The cells with the given bits set, bits outside of the board are ignored.
+    pub fn from_bits(bits: T) -> Self {
+        BitBoard(bits & Self::full().0)
+    }
+
+    pub fn bits(self) -> T {
+        self.0
+    }
+
+    pub fn coord(x: u8, y: u8) -> Self {
+        Self::empty().set(x, y)
+    }
+
+    pub fn has(self, x: u8, y: u8) -> bool {
+        self.0.has(Self::index(x, y))
+    }
+
+    #[must_use]
+    pub fn set(self, x: u8, y: u8) -> Self {
+        BitBoard(self.0 | T::bit(Self::index(x, y)))
+    }
+
+    #[must_use]
+    pub fn clear(self, x: u8, y: u8) -> Self {
+        BitBoard(self.0 & !T::bit(Self::index(x, y)))
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == T::zero()
+    }
+
+    /// Iterate over the set cells as `(x, y)`, starting from the lowest index.
+    pub fn iter(self) -> impl Iterator<Item = (u8, u8)> {
+        let mut left = self.0;
+        std::iter::from_fn(move || {
+            let index = left.trailing_zeros();
+            if index >= Self::CELLS {
+                return None;
+            }
+            left = left & !T::bit(index);
+            Some(((index % W as u32) as u8, (index / W as u32) as u8))
+        })
+    }
+
+    /// Move every cell one step towards `x = 0`.
+    pub fn left(self) -> Self {
+        BitBoard((self.0 & !Self::column(0)).shift_down(1))
+    }
+
+    /// Move every cell one step towards `x = W - 1`.
+    pub fn right(self) -> Self {
+        BitBoard((self.0 & !Self::column(W - 1)).shift_up(1))
+    }
+
+    /// Move every cell one step towards `y = 0`.
+    pub fn down(self) -> Self {
+        BitBoard(self.0.shift_down(W as u32))
+    }
+
+    /// Move every cell one step towards `y = H - 1`.
+    pub fn up(self) -> Self {
+        BitBoard(self.0.shift_up(W as u32) & Self::full().0)
+    }
+
+    /// The cells next to a set cell, including diagonally. These are the targets of copy moves in Ataxx.
+    pub fn adjacent(self) -> Self {
+        let sides = self.left() | self.right();
+        let row = sides | self;
+        sides | row.up() | row.down()
+    }
+
+    /// The cells two steps away from a set cell, including diagonally and knight-like offsets.
+    /// These are the targets of jump moves in Ataxx.
+    pub fn distance_two(self) -> Self {
+        let (left, right) = (self.left(), self.right());
+        let sides = left.left() | right.right();
+        let row = sides | left | right | self;
+        sides | sides.up() | sides.down() | row.up().up() | row.down().down()
+    }
+
+    fn index(x: u8, y: u8) -> u32 {
+        assert!(x < W && y < H, "({}, {}) is outside of the {}x{} board", x, y, W, H);
+        y as u32 * W as u32 + x as u32
+    }
+
+    fn column(x: u8) -> T {
+        let mut bits = T::zero();
+        for y in 0..H {
+            bits = bits | T::bit(Self::index(x, y));
+        }
+        bits
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> BitAnd for BitBoard<T, W, H> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        BitBoard(self.0 & rhs.0)
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> BitOr for BitBoard<T, W, H> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        BitBoard(self.0 | rhs.0)
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> BitXor for BitBoard<T, W, H> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        BitBoard(self.0 ^ rhs.0)
+    }
+}
+
+/// The cells of the board that are not set.
+impl<T: Bits, const W: u8, const H: u8> Not for BitBoard<T, W, H> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        BitBoard(!self.0 & Self::full().0)
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> BitAndAssign for BitBoard<T, W, H> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> BitOrAssign for BitBoard<T, W, H> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs
+    }
+}
+
+impl<T: Bits, const W: u8, const H: u8> Debug for BitBoard<T, W, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BitBoard({:?})", self.iter().collect::<Vec<_>>())
+    }
+}
+
+/// Draws the board with `y = 0` at the bottom, set cells as `1` and the other cells as `.`.
+impl<T: Bits, const W: u8, const H: u8> Display for BitBoard<T, W, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in (0..H).rev() {
+            for x in 0..W {
+                write!(f, "{}", if self.has(x, y) { '1' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
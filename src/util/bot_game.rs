@@ -1,23 +1,36 @@
 //! Utilities to run bots against each other and report the results.
+use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Write;
+use std::io;
 use std::ops::Add;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::time::Instant;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
+use serde_json::{json, Value};
 
+use crate::ai::external::ExternalBoard;
 use crate::ai::Bot;
-use crate::board::{Board, Outcome};
+use crate::board::{Board, Outcome, Player};
+use crate::util::elo::{
+    elo_estimate, pentanomial_significance, trinomial_significance, Hypothesis, Significance, Sprt,
+};
+use crate::util::replay::ReplayRecorder;
 
 /// Run `bot_l` against `bot_r` against each other on the board given by `start`.
 ///
 /// `games_per_side` games are run, except if `both_sides` is true, in
 /// which case a match consists of two games where players switch sides.
 ///
-/// Progress indications can be displayed at intervals of `print_progress_every`.
+/// To follow the progress of the match, use [run_with] with [MatchOptions::results].
 #[must_use]
 pub fn run<B: Board, L: Bot<B>, R: Bot<B>>(
     start: impl Fn() -> B + Sync,
@@ -25,132 +38,963 @@ pub fn run<B: Board, L: Bot<B>, R: Bot<B>>(
     bot_r: impl Fn() -> R + Sync,
     games_per_side: u32,
     both_sides: bool,
-    print_progress_every: Option<u32>,
 ) -> BotGameResult {
-    // this instantiates both at least once so we catch errors before starting a bunch of threads
-    let debug_l = debug_to_string(&bot_l());
-    let debug_r = debug_to_string(&bot_r());
+    run_observed(start, bot_l, bot_r, games_per_side, both_sides, |_: &GameEvent<B>| {})
+}
+
+/// An event during one of the games of [run_observed].
+#[derive(Debug)]
+pub enum GameEvent<'a, B: Board> {
+    /// Game `game_id` starts from `board`.
+    Start { game_id: u32, board: &'a B },
+    /// `mv` was played on `board` by the left bot if `left` is true, otherwise by the right bot.
+    /// `time` is the time the bot took in seconds and `value` is the evaluation the bot reported, if any,
+    /// see [select_move_with_value](Bot::select_move_with_value).
+    Move {
+        game_id: u32,
+        board: &'a B,
+        mv: B::Move,
+        left: bool,
+        time: f32,
+        value: Option<f32>,
+    },
+    /// Game `game_id` ended on `board` with `outcome`.
+    End {
+        game_id: u32,
+        board: &'a B,
+        outcome: Outcome,
+    },
+}
+
+/// Same as [run], but `observer` is called for every [GameEvent], which allows following the games live.
+/// Games are played in parallel so the events of different games are interleaved.
+#[must_use]
+pub fn run_observed<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    run_impl(
+        same_start(start),
+        |_| bot_l(),
+        |_| bot_r(),
+        games_per_side,
+        both_sides,
+        None,
+        &MatchOptions::default(),
+        observer,
+    )
+}
+
+/// Same as [run_observed], but with the extra settings in `options`.
+#[must_use]
+pub fn run_with<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    options: &MatchOptions<B>,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    run_impl(
+        same_start(start),
+        |_| bot_l(),
+        |_| bot_r(),
+        games_per_side,
+        both_sides,
+        None,
+        options,
+        observer,
+    )
+}
+
+/// Same as [run_with], but the bots are built from a seed that is derived from `seed` and the game index,
+/// so every game can be reproduced. The seeds each game used are reported in [GameSummary::seeds].
+///
+/// The bots should use the seed for all their randomness, for example with
+/// `|seed| RandomBot::new(StdRng::seed_from_u64(seed))`. Games with a time control can't be reproduced exactly,
+/// since the amount of search a bot does depends on the speed of the machine.
+#[must_use]
+pub fn run_seeded<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn(u64) -> L + Sync,
+    bot_r: impl Fn(u64) -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    seed: u64,
+    options: &MatchOptions<B>,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    run_impl(
+        same_start(start),
+        bot_l,
+        bot_r,
+        games_per_side,
+        both_sides,
+        Some(seed),
+        options,
+        observer,
+    )
+}
+
+/// The seeds for the left and right bot in game `game_id` of a match run by [run_seeded] with `seed`.
+pub fn game_seeds(seed: u64, game_id: u32) -> (u64, u64) {
+    let game_seed = split_mix(seed ^ split_mix(game_id as u64));
+    (split_mix(game_seed), split_mix(game_seed ^ 1))
+}
+
+/// The SplitMix64 mixing function, see <https://prng.di.unimi.it/splitmix64.c>.
+fn split_mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Same as [run_with], but every game is also written to `output` in the [replay](crate::util::replay) format
+/// once it ends, including the time and value of every move, see [ReplayRecorder].
+///
+/// Returns the result and the output, or the first error that happened while writing to it.
+pub fn run_recorded<B: ExternalBoard, L: Bot<B>, R: Bot<B>, W: io::Write + Send>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    options: &MatchOptions<B>,
+    output: W,
+) -> (BotGameResult, io::Result<W>) {
+    let recorder = ReplayRecorder::new(output);
+    let result = run_with(start, bot_l, bot_r, games_per_side, both_sides, options, |event| {
+        recorder.observe(event)
+    });
+    (result, recorder.finish())
+}
+
+/// Same as [run_observed], but the match stops as soon as `sprt` accepts one of its hypotheses,
+/// or after `max_games_per_side` games if it doesn't.
+///
+/// Games that were already running when the test concluded are still finished and included in the result,
+/// but not in the test itself. If `both_sides` is true, the test is updated once both games of a pair are finished,
+/// so every start position is always played with both colors. The outcome of the test is reported in
+/// [BotGameResult::sprt].
+#[must_use]
+pub fn run_sprt<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    max_games_per_side: u32,
+    both_sides: bool,
+    sprt: Sprt,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    let options = MatchOptions {
+        sprt: Some(sprt),
+        ..MatchOptions::default()
+    };
+    run_with(start, bot_l, bot_r, max_games_per_side, both_sides, &options, observer)
+}
+
+/// Same as [run_observed], but every position in `openings` is played twice, once with each bot moving first.
+///
+/// Most games have a first-move advantage, playing both sides of a varied set of openings
+/// is needed to compare bots fairly. Opening suites can be loaded with [parse_openings].
+#[must_use]
+pub fn run_openings<B: Board, L: Bot<B>, R: Bot<B>>(
+    openings: &[B],
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    assert!(!openings.is_empty(), "At least one opening is required");
+
+    // the starts are generated in order before any game is played
+    let next_opening = AtomicUsize::new(0);
+    run_impl(
+        same_start(|| openings[next_opening.fetch_add(1, Ordering::Relaxed)].clone()),
+        |_| bot_l(),
+        |_| bot_r(),
+        openings.len() as u32,
+        true,
+        None,
+        &MatchOptions::default(),
+        observer,
+    )
+}
+
+/// A pair of start positions for [run_conditions], one for the games where the left bot moves first and one for the
+/// games where the right bot moves first. This allows giving one of the bots a handicap, see [Condition::handicap].
+#[derive(Debug, Clone)]
+pub struct Condition<B: Board> {
+    pub name: String,
+    pub start_l_first: B,
+    pub start_r_first: B,
+}
+
+impl<B: Board> Condition<B> {
+    pub fn new(name: impl Into<String>, start_l_first: B, start_r_first: B) -> Self {
+        Condition {
+            name: name.into(),
+            start_l_first,
+            start_r_first,
+        }
+    }
+
+    /// The condition where both bots start from `start`, to compare against the handicap conditions.
+    pub fn even(name: impl Into<String>, start: B) -> Self {
+        Condition::new(name, start.clone(), start)
+    }
+
+    /// The condition where the left bot plays with a handicap, whichever side it plays.
+    /// `handicap(start, player)` should return `start` with a handicap applied to `player`,
+    /// for example [AtaxxBoard::with_handicap](crate::games::ataxx::AtaxxBoard::with_handicap).
+    pub fn handicap(name: impl Into<String>, start: &B, handicap: impl Fn(&B, Player) -> B) -> Self {
+        let first = start.next_player();
+        Condition::new(name, handicap(start, first), handicap(start, first.other()))
+    }
+}
+
+/// Run a match for each condition, where every game pair is played once from each start of the condition.
+/// Returns the results in the same order as `conditions`.
+///
+/// Comparing the results of an even condition against handicap conditions shows how large the strength gap between
+/// very unequal bots is, which is hard to measure when one of them wins almost every game.
+/// The game ids passed to `observer` restart from zero for every condition.
+#[must_use]
+pub fn run_conditions<B: Board, L: Bot<B>, R: Bot<B>>(
+    conditions: &[Condition<B>],
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    options: &MatchOptions<B>,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> Vec<BotGameResult> {
+    conditions
+        .iter()
+        .map(|condition| {
+            run_impl(
+                || (condition.start_l_first.clone(), condition.start_r_first.clone()),
+                |_| bot_l(),
+                |_| bot_r(),
+                games_per_side,
+                true,
+                None,
+                options,
+                &observer,
+            )
+        })
+        .collect()
+}
+
+/// Parse an opening suite with one FEN per line, as used by [run_openings].
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_openings<B: ExternalBoard>(content: &str) -> Result<Vec<B>, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|fen| B::external_from_fen(fen).ok_or_else(|| format!("Invalid opening '{}'", fen)))
+        .collect()
+}
+
+/// Extra settings for [run_with], the default plays games without any limits until the end.
+#[derive(Debug, Clone)]
+pub struct MatchOptions<B: Board> {
+    /// Stop the match as soon as this test accepts one of its hypotheses, see [run_sprt].
+    pub sprt: Option<Sprt>,
+    /// The time control both bots play with.
+    pub time_control: Option<TimeControl>,
+    /// Rules to end games early.
+    pub adjudication: Option<Adjudication<B>>,
+    /// Catch panics in a game, which then counts as a [PanickedGame] instead of aborting the whole match.
+    pub catch_panics: bool,
+    /// After every game the results so far are written to this file as JSON, see [write_status].
+    pub status_file: Option<PathBuf>,
+    /// A [GameSummary] is sent to this channel as soon as each game ends, including games that panicked.
+    /// This can be used to follow the progress of the match. Sending errors are ignored,
+    /// so the receiver can be dropped at any time.
+    pub results: Option<Sender<GameSummary<B>>>,
+}
+
+impl<B: Board> Default for MatchOptions<B> {
+    fn default() -> Self {
+        MatchOptions {
+            sprt: None,
+            time_control: None,
+            adjudication: None,
+            catch_panics: false,
+            status_file: None,
+            results: None,
+        }
+    }
+}
+
+/// A game that was aborted because it panicked, see [MatchOptions::catch_panics].
+#[derive(Debug, Clone)]
+pub struct PanickedGame {
+    pub game_id: u32,
+    /// Whether the left bot played the first move.
+    pub left_first: bool,
+    pub message: String,
+    /// The board when the panic happened, formatted with [Debug].
+    pub board: String,
+    /// The moves played before the panic, formatted with [Debug].
+    pub moves: Vec<String>,
+}
+
+/// The result of a single game of a match, see [MatchOptions::results].
+#[derive(Debug, Clone)]
+pub struct GameSummary<B: Board> {
+    pub game_id: u32,
+    // bot debug strings
+    pub bot_l: String,
+    pub bot_r: String,
+    /// The board the game started from.
+    pub start: B,
+    /// Whether the left bot played the first move.
+    pub left_first: bool,
+    /// The outcome of the game, `None` if it panicked.
+    pub outcome: Option<Outcome>,
+    /// The panic that aborted the game, see [MatchOptions::catch_panics].
+    pub panic: Option<PanickedGame>,
+    /// Whether the game was lost on time.
+    pub forfeit: bool,
+    /// Whether the game was ended by [MatchOptions::adjudication].
+    pub adjudicated: bool,
+    /// The seeds the bots were built with, only for matches run by [run_seeded].
+    pub seeds: Option<(u64, u64)>,
+    pub move_count: u32,
+    // total time used in seconds
+    pub time_l: f32,
+    pub time_r: f32,
+}
+
+/// A time control for the games of a match, with a clock per game, a limit per move or both.
+///
+/// Before each move the bot is given a time budget through [select_move_timed](Bot::select_move_timed),
+/// a bot that exceeds the limit or runs out of time on its clock loses the game on time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeControl {
+    /// The time on the clock of each bot at the start of a game.
+    pub base: Option<Duration>,
+    /// The time added to the clock of a bot after each of its moves.
+    pub increment: Duration,
+    /// The maximum time for a single move.
+    pub per_move: Option<Duration>,
+}
+
+impl TimeControl {
+    /// A clock with `base` time per game and `increment` added after each move.
+    pub fn per_game(base: Duration, increment: Duration) -> Self {
+        TimeControl {
+            base: Some(base),
+            increment,
+            per_move: None,
+        }
+    }
+
+    /// A fixed maximum time per move.
+    pub fn per_move(time: Duration) -> Self {
+        TimeControl {
+            base: None,
+            increment: Duration::default(),
+            per_move: Some(time),
+        }
+    }
+
+    /// The time budget for the next move given the time left on the `clock`, `None` if there is no limit at all.
+    /// The budget is kept a bit below the hard limit so bots have some slack to return their move.
+    pub fn budget(&self, clock: Option<Duration>) -> Option<Duration> {
+        let from_clock = clock.map(|clock| (clock / MOVES_TO_GO + self.increment).min(clock));
+        let limit = match (from_clock, self.per_move) {
+            (None, None) => return None,
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) | (None, Some(a)) => a,
+        };
+        Some(limit.mul_f32(BUDGET_FRACTION))
+    }
+
+    /// Charge a move that took `elapsed` to `clock`, returns whether the bot lost on time.
+    fn charge(&self, clock: &mut Option<Duration>, elapsed: Duration) -> bool {
+        if matches!(self.per_move, Some(limit) if elapsed > limit) {
+            return true;
+        }
+        match clock {
+            None => false,
+            Some(clock) if elapsed > *clock => true,
+            Some(clock) => {
+                *clock = *clock - elapsed + self.increment;
+                false
+            }
+        }
+    }
+}
+
+/// Rules to end games before they're finished, to save time on games whose result is already clear.
+/// All rules are optional, the default doesn't adjudicate at all.
+pub struct Adjudication<B: Board> {
+    /// Declare a draw after this many moves.
+    pub max_moves: Option<u32>,
+    /// `(count, key)`: declare a draw when the same position occurs `count` times.
+    /// Positions are compared by `key`, which should leave out move counters and the like,
+    /// for example [ChessBoard::repetition_key](crate::games::chess::ChessBoard::repetition_key)
+    /// or [AtaxxBoard::zobrist](crate::games::ataxx::AtaxxBoard::zobrist).
+    pub repetitions: Option<(u32, PositionKey<B>)>,
+    /// `(threshold, moves)`: declare a win when the values reported for `moves` consecutive moves,
+    /// alternating between both bots, are all at least `threshold` for the same player.
+    /// Bots that don't report values are never adjudicated as winning, see
+    /// [select_move_with_value](Bot::select_move_with_value).
+    pub win: Option<(f32, u32)>,
+}
+
+/// A function that identifies a position, see [Adjudication::repetitions].
+pub type PositionKey<B> = fn(&B) -> u64;
+
+impl<B: Board> Default for Adjudication<B> {
+    fn default() -> Self {
+        Adjudication {
+            max_moves: None,
+            repetitions: None,
+            win: None,
+        }
+    }
+}
+
+// implemented manually because the derives would require B to implement the traits as well
+impl<B: Board> Clone for Adjudication<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B: Board> Copy for Adjudication<B> {}
+
+impl<B: Board> Debug for Adjudication<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Adjudication")
+            .field("max_moves", &self.max_moves)
+            .field("repetitions", &self.repetitions.map(|(count, _)| count))
+            .field("win", &self.win)
+            .finish()
+    }
+}
+
+/// Tracks a single game for the [Adjudication] rules.
+struct Adjudicator<B: Board> {
+    rules: Adjudication<B>,
+    moves: u32,
+    positions: HashMap<u64, u32>,
+    // the player both bots think is winning and for how many moves they agreed
+    streak: Option<(Player, u32)>,
+}
+
+impl<B: Board> Adjudicator<B> {
+    fn new(rules: Adjudication<B>, start: &B) -> Self {
+        let mut positions = HashMap::new();
+        if let Some((_, key)) = rules.repetitions {
+            positions.insert(key(start), 1);
+        }
+        Adjudicator {
+            rules,
+            moves: 0,
+            positions,
+            streak: None,
+        }
+    }
+
+    /// Update the state after a move by `player` that resulted in `board`, for which the bot reported `value`.
+    /// Returns the adjudicated outcome if the game should end now.
+    fn play(&mut self, player: Player, board: &B, value: Option<f32>) -> Option<Outcome> {
+        self.moves += 1;
+
+        if let Some((threshold, moves)) = self.rules.win {
+            let winner = value.and_then(|value| {
+                if value >= threshold {
+                    Some(player)
+                } else if value <= -threshold {
+                    Some(player.other())
+                } else {
+                    None
+                }
+            });
+            self.streak = match (winner, self.streak) {
+                (None, _) => None,
+                (Some(winner), Some((prev, count))) if winner == prev => Some((winner, count + 1)),
+                (Some(winner), _) => Some((winner, 1)),
+            };
+            if let Some((winner, count)) = self.streak {
+                if count >= moves {
+                    return Some(Outcome::WonBy(winner));
+                }
+            }
+        }
+
+        if let Some(max_moves) = self.rules.max_moves {
+            if self.moves >= max_moves {
+                return Some(Outcome::Draw);
+            }
+        }
+
+        if let Some((repetitions, key)) = self.rules.repetitions {
+            let count = self.positions.entry(key(board)).or_insert(0);
+            *count += 1;
+            if *count >= repetitions {
+                return Some(Outcome::Draw);
+            }
+        }
+
+        None
+    }
+}
+
+/// The number of moves a bot is expected to still play when splitting up its clock.
+const MOVES_TO_GO: u32 = 30;
+/// The fraction of the time limit bots are told they can use.
+const BUDGET_FRACTION: f32 = 0.9;
+
+/// The outcome of the [Sprt] of a match played by [run_sprt].
+#[derive(Debug, Clone)]
+pub struct SprtResult {
+    pub sprt: Sprt,
+    /// The accepted hypothesis, `None` if the maximum number of games was reached first.
+    pub accepted: Option<Hypothesis>,
+    /// The log-likelihood ratio after each game, or each pair of games if both sides were played,
+    /// that was included in the test.
+    pub llr: Vec<f32>,
+}
+
+#[derive(Debug, Default)]
+struct SprtState {
+    wins_l: u32,
+    draws: u32,
+    wins_r: u32,
+    accepted: Option<Hypothesis>,
+    llr: Vec<f32>,
+}
+
+/// Use the same start for the games where the left and the right bot move first.
+fn same_start<B: Board>(start: impl Fn() -> B + Sync) -> impl Fn() -> (B, B) + Sync {
+    move || {
+        let start = start();
+        (start.clone(), start)
+    }
+}
+
+fn run_impl<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> (B, B) + Sync,
+    bot_l: impl Fn(u64) -> L + Sync,
+    bot_r: impl Fn(u64) -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    seed: Option<u64>,
+    options: &MatchOptions<B>,
+    observer: impl Fn(&GameEvent<B>) + Sync,
+) -> BotGameResult {
+    let sprt = options.sprt;
+    let time_control = options.time_control;
+    let catch_panics = options.catch_panics;
+    let adjudication = options.adjudication;
+    // senders can't be shared between threads on older compilers
+    let results = options.results.clone().map(Mutex::new);
+
+    // taken from the first bots built for a game, building extra bots can be expensive (eg. external engines)
+    let debug_l = Mutex::new(None);
+    let debug_r = Mutex::new(None);
+    let debug = |debug: &Mutex<Option<String>>| debug.lock().unwrap().clone().unwrap_or_default();
 
-    let progress_counter = AtomicU32::default();
     let game_count = if both_sides { 2 * games_per_side } else { games_per_side };
 
     let starts = (0..games_per_side).map(|_| start()).collect_vec();
 
-    let result: ReductionResult = (0..games_per_side)
-        .into_par_iter()
-        .panic_fuse()
-        .map(|game_i| {
-            let pair_i = if both_sides { game_i / 2 } else { game_i };
-            let start = starts[pair_i as usize].clone();
+    let stop = AtomicBool::new(false);
+    let sprt_state = Mutex::new(SprtState::default());
+    // wins of the left bot, draws and wins of the right bot so far
+    let tally = Mutex::new((0, 0, 0));
+    // the number of pairs where the left bot scored 0 to 4 half points
+    let pairs = Mutex::new([0; 5]);
+
+    // game `2 * i` of a pair is played with the left bot moving first, game `2 * i + 1` with the right bot
+    let play_game = |game_i: u32| -> ReductionResult {
+        let pair_i = if both_sides { game_i / 2 } else { game_i };
+        let flip = if both_sides { game_i % 2 == 1 } else { false };
+        let (start_l_first, start_r_first) = &starts[pair_i as usize];
+        let start = if flip { start_r_first } else { start_l_first };
 
-            let mut bot_l = bot_l();
-            let mut bot_r = bot_r();
+        let seeds = seed.map(|seed| game_seeds(seed, game_i));
+        let (seed_l, seed_r) = seeds.unwrap_or_default();
+        let mut total_time_l = 0.0;
+        let mut total_time_r = 0.0;
+        let mut move_count_l: u32 = 0;
+        let mut move_count_r: u32 = 0;
+        let mut move_times_l = vec![];
+        let mut move_times_r = vec![];
 
-            let mut total_time_l = 0.0;
-            let mut total_time_r = 0.0;
-            let mut move_count_l: u32 = 0;
-            let mut move_count_r: u32 = 0;
+        let mut clock_l = time_control.and_then(|tc| tc.base);
+        let mut clock_r = clock_l;
+        // whether the left bot lost on time and the resulting outcome
+        let mut forfeit = None;
+        let mut adjudicator = adjudication.map(|rules| Adjudicator::new(rules, start));
+        let mut adjudicated = None;
 
-            let flip = if both_sides { game_i % 2 == 1 } else { false };
-            let mut board = start;
-            let player_first = board.next_player();
+        let mut board = start.clone();
+        let mut moves = vec![];
+        let player_first = board.next_player();
+
+        // the state above is kept outside so it can be reported if the game panics
+        let played = catch_unwind(AssertUnwindSafe(|| {
+            let mut bot_l = bot_l(seed_l);
+            let mut bot_r = bot_r(seed_r);
+            debug_l.lock().unwrap().get_or_insert_with(|| debug_to_string(&bot_l));
+            debug_r.lock().unwrap().get_or_insert_with(|| debug_to_string(&bot_r));
+
+            observer(&GameEvent::Start {
+                game_id: game_i,
+                board: &board,
+            });
 
             for move_i in 0.. {
                 if board.is_done() {
                     break;
                 }
 
-                let start = Instant::now();
-                let mv = if flip ^ (move_i % 2 == 0) {
-                    let mv = bot_l.select_move(&board);
-                    total_time_l += (Instant::now() - start).as_secs_f32();
+                let move_start = Instant::now();
+                let left = flip ^ (move_i % 2 == 0);
+                let budget = time_control.and_then(|tc| tc.budget(if left { clock_l } else { clock_r }));
+                let (mv, value) = match (left, budget) {
+                    (true, None) => bot_l.select_move_with_value(&board),
+                    (true, Some(budget)) => bot_l.select_move_timed(&board, budget),
+                    (false, None) => bot_r.select_move_with_value(&board),
+                    (false, Some(budget)) => bot_r.select_move_timed(&board, budget),
+                };
+                let elapsed = Instant::now() - move_start;
+                let time = elapsed.as_secs_f32();
+
+                if left {
+                    total_time_l += time;
                     move_count_l += 1;
-                    mv
+                    move_times_l.push(time);
                 } else {
-                    let mv = bot_r.select_move(&board);
-                    total_time_r += (Instant::now() - start).as_secs_f32();
+                    total_time_r += time;
                     move_count_r += 1;
-                    mv
-                };
+                    move_times_r.push(time);
+                }
+
+                if let Some(tc) = time_control {
+                    if tc.charge(if left { &mut clock_l } else { &mut clock_r }, elapsed) {
+                        forfeit = Some((left, Outcome::WonBy(board.next_player().other())));
+                        break;
+                    }
+                }
 
+                observer(&GameEvent::Move {
+                    game_id: game_i,
+                    board: &board,
+                    mv,
+                    left,
+                    time,
+                    value,
+                });
+                let player = board.next_player();
+                moves.push(mv);
                 board.play(mv);
-            }
 
-            if let Some(print_progress) = print_progress_every {
-                let progress = progress_counter.fetch_add(1, Ordering::Relaxed) + 1;
-                if progress % print_progress == 0 {
-                    println!("Progress: {}", progress as f32 / games_per_side as f32);
+                if let Some(adjudicator) = &mut adjudicator {
+                    if !board.is_done() {
+                        adjudicated = adjudicator.play(player, &board, value);
+                        if adjudicated.is_some() {
+                            break;
+                        }
+                    }
                 }
             }
+        }));
 
-            // SAFETY: unwrap is safe because we could only break out of the
-            // for loop if `board.is_done()` is true.
-            let outcome = board.outcome().unwrap();
-            let win_first = (outcome == Outcome::WonBy(player_first)) as u32;
-            let win_second = (outcome == Outcome::WonBy(player_first.other())) as u32;
+        if let Err(payload) = played {
+            if !catch_panics {
+                resume_unwind(payload);
+            }
+            let panicked = PanickedGame {
+                game_id: game_i,
+                left_first: !flip,
+                message: panic_message(&*payload),
+                board: format!("{:?}", board),
+                moves: moves.iter().map(|mv| format!("{:?}", mv)).collect(),
+            };
+            if let Some(results) = &results {
+                let summary = GameSummary {
+                    game_id: game_i,
+                    bot_l: debug(&debug_l),
+                    bot_r: debug(&debug_r),
+                    start: start.clone(),
+                    left_first: !flip,
+                    outcome: None,
+                    panic: Some(panicked.clone()),
+                    forfeit: false,
+                    adjudicated: false,
+                    seeds,
+                    move_count: move_count_l + move_count_r,
+                    time_l: total_time_l,
+                    time_r: total_time_r,
+                };
+                let _ = results.lock().unwrap().send(summary);
+            }
+            return ReductionResult {
+                panics: vec![panicked],
+                ..ReductionResult::default()
+            };
+        }
 
-            let (wins_l, wins_r) = if flip {
-                (win_second, win_first)
+        // SAFETY: unwrap is safe because we could only break out of the
+        // for loop if `board.is_done()` is true, after a forfeit or after adjudication.
+        let outcome = forfeit
+            .map(|(_, outcome)| outcome)
+            .or(adjudicated)
+            .unwrap_or_else(|| board.outcome().unwrap());
+        observer(&GameEvent::End {
+            game_id: game_i,
+            board: &board,
+            outcome,
+        });
+        let win_first = (outcome == Outcome::WonBy(player_first)) as u32;
+        let win_second = (outcome == Outcome::WonBy(player_first.other())) as u32;
+
+        let (wins_l, wins_r) = if flip {
+            (win_second, win_first)
+        } else {
+            (win_first, win_second)
+        };
+
+        if let Some(path) = &options.status_file {
+            let mut tally = tally.lock().unwrap();
+            tally.0 += wins_l;
+            tally.1 += 1 - wins_l - wins_r;
+            tally.2 += wins_r;
+
+            let (wins_l, draws, wins_r) = *tally;
+            let elo = elo_estimate(wins_l, draws, wins_r);
+            let status = json!({
+                "bot_l": debug(&debug_l),
+                "bot_r": debug(&debug_r),
+                "games": wins_l + draws + wins_r,
+                "max_games": game_count,
+                "wins_l": wins_l,
+                "draws": draws,
+                "wins_r": wins_r,
+                "elo_l": elo.elo,
+                "elo_error_l": elo.error,
+                "los_l": elo.los,
+            });
+            // the status is only informative, a failure should not abort the match
+            if let Err(e) = write_status(path, &status) {
+                eprintln!("Failed to write status to {:?}: {}", path, e);
+            }
+        }
+
+        if let Some(results) = &results {
+            let summary = GameSummary {
+                game_id: game_i,
+                bot_l: debug(&debug_l),
+                bot_r: debug(&debug_r),
+                start: start.clone(),
+                left_first: !flip,
+                outcome: Some(outcome),
+                panic: None,
+                forfeit: forfeit.is_some(),
+                adjudicated: adjudicated.is_some(),
+                seeds,
+                move_count: move_count_l + move_count_r,
+                time_l: total_time_l,
+                time_r: total_time_r,
+            };
+            let _ = results.lock().unwrap().send(summary);
+        }
+
+        ReductionResult {
+            games: 1,
+            wins_l,
+            wins_r,
+            forfeits_l: matches!(forfeit, Some((true, _))) as u32,
+            forfeits_r: matches!(forfeit, Some((false, _))) as u32,
+            adjudicated: adjudicated.is_some() as u32,
+            total_time_l,
+            total_time_r,
+            move_count_l,
+            move_count_r,
+            move_times_l,
+            move_times_r,
+            panics: vec![],
+        }
+    };
+
+    // both games of a pair are played by the same thread, so a pair is always finished once it started,
+    // even if the match is stopped early
+    let result: ReductionResult = (0..games_per_side)
+        .into_par_iter()
+        .panic_fuse()
+        .map(|pair_i| {
+            if stop.load(Ordering::Relaxed) {
+                return ReductionResult::default();
+            }
+
+            let games = if both_sides {
+                vec![play_game(2 * pair_i), play_game(2 * pair_i + 1)]
             } else {
-                (win_first, win_second)
+                vec![play_game(pair_i)]
             };
+            let result = games.into_iter().fold(ReductionResult::default(), ReductionResult::add);
+            let draws = result.games - result.wins_l - result.wins_r;
+
+            // pairs with a panicked game are incomplete
+            if both_sides && result.games == 2 {
+                pairs.lock().unwrap()[(2 * result.wins_l + draws) as usize] += 1;
+            }
 
-            ReductionResult {
-                wins_l,
-                wins_r,
-                total_time_l,
-                total_time_r,
-                move_count_l,
-                move_count_r,
+            if let Some(sprt) = sprt {
+                let mut state = sprt_state.lock().unwrap();
+                if state.accepted.is_none() {
+                    state.wins_l += result.wins_l;
+                    state.wins_r += result.wins_r;
+                    state.draws += draws;
+
+                    let llr = sprt.llr(state.wins_l, state.draws, state.wins_r);
+                    state.llr.push(llr);
+                    state.accepted = sprt.decide(llr);
+                    if state.accepted.is_some() {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
             }
+
+            result
         })
         .reduce(ReductionResult::default, ReductionResult::add);
 
+    let game_count = result.games;
     let draws = game_count - result.wins_l - result.wins_r;
-    let score_l = (result.wins_l as f32 + 0.5 * draws as f32) / (game_count as f32);
-    let elo = -400.0 * (1.0 / score_l - 1.0).log10();
+    let elo = elo_estimate(result.wins_l, draws, result.wins_r);
 
     BotGameResult {
         game_count,
-        game_length: (result.move_count_l + result.move_count_r) as f32 / (game_count) as f32,
-        win_rate_l: (result.wins_l as f32) / (game_count as f32),
-        draw_rate: (draws as f32) / (game_count as f32),
-        win_rate_r: (result.wins_r as f32) / (game_count as f32),
-        elo_l: elo,
-        time_l: result.total_time_l / (result.move_count_l as f32),
-        time_r: result.total_time_r / (result.move_count_r as f32),
-        debug_l,
-        debug_r,
+        game_length: average((result.move_count_l + result.move_count_r) as f32, game_count),
+        wins_l: result.wins_l,
+        draws,
+        wins_r: result.wins_r,
+        win_rate_l: average(result.wins_l as f32, game_count),
+        draw_rate: average(draws as f32, game_count),
+        win_rate_r: average(result.wins_r as f32, game_count),
+        elo_l: elo.elo,
+        elo_error_l: elo.error,
+        los_l: elo.los,
+        time_l: average(result.total_time_l, result.move_count_l),
+        time_r: average(result.total_time_r, result.move_count_r),
+        move_times_l: MoveTimes::new(result.move_times_l),
+        move_times_r: MoveTimes::new(result.move_times_r),
+        time_forfeits_l: result.forfeits_l,
+        time_forfeits_r: result.forfeits_r,
+        adjudicated: result.adjudicated,
+        panics: result.panics,
+        pairs: if both_sides {
+            Some(pairs.into_inner().unwrap())
+        } else {
+            None
+        },
+        debug_l: debug_l.into_inner().unwrap().unwrap_or_default(),
+        debug_r: debug_r.into_inner().unwrap().unwrap_or_default(),
+        sprt: sprt.map(|sprt| {
+            let state = sprt_state.into_inner().unwrap();
+            SprtResult {
+                sprt,
+                accepted: state.accepted,
+                llr: state.llr,
+            }
+        }),
     }
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+/// `total / count`, or `0` if `count` is zero, for example when every game panicked.
+fn average(total: f32, count: u32) -> f32 {
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+#[derive(Default, Debug, Clone)]
 struct ReductionResult {
+    games: u32,
     wins_l: u32,
     wins_r: u32,
+    forfeits_l: u32,
+    forfeits_r: u32,
+    adjudicated: u32,
     total_time_l: f32,
     total_time_r: f32,
     move_count_l: u32,
     move_count_r: u32,
+    move_times_l: Vec<f32>,
+    move_times_r: Vec<f32>,
+    panics: Vec<PanickedGame>,
 }
 
 impl std::ops::Add for ReductionResult {
     type Output = Self;
 
-    fn add(self, rhs: Self) -> Self::Output {
+    fn add(mut self, mut rhs: Self) -> Self::Output {
+        self.panics.append(&mut rhs.panics);
+        self.move_times_l.append(&mut rhs.move_times_l);
+        self.move_times_r.append(&mut rhs.move_times_r);
         ReductionResult {
+            games: self.games + rhs.games,
             wins_l: self.wins_l + rhs.wins_l,
             wins_r: self.wins_r + rhs.wins_r,
+            forfeits_l: self.forfeits_l + rhs.forfeits_l,
+            forfeits_r: self.forfeits_r + rhs.forfeits_r,
+            adjudicated: self.adjudicated + rhs.adjudicated,
             total_time_l: self.total_time_l + rhs.total_time_l,
             total_time_r: self.total_time_r + rhs.total_time_r,
             move_count_l: self.move_count_l + rhs.move_count_l,
             move_count_r: self.move_count_r + rhs.move_count_r,
+            move_times_l: self.move_times_l,
+            move_times_r: self.move_times_r,
+            panics: self.panics,
+        }
+    }
+}
+
+/// The distribution of the time a bot took per move in a match, in seconds. All zero if the bot didn't move.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MoveTimes {
+    /// The median.
+    pub p50: f32,
+    /// The time 95% of the moves took at most.
+    pub p95: f32,
+    pub max: f32,
+}
+
+impl MoveTimes {
+    fn new(mut times: Vec<f32>) -> Self {
+        if times.is_empty() {
+            return MoveTimes::default();
+        }
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // the nearest-rank percentile
+        let percentile = |p: f32| times[((p * times.len() as f32).ceil() as usize).max(1) - 1];
+        MoveTimes {
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            max: *times.last().unwrap(),
         }
     }
 }
 
 /// Structure returned by the function [`run`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BotGameResult {
     pub game_count: u32,
 
@@ -158,20 +1002,169 @@ pub struct BotGameResult {
     pub game_length: f32,
 
     //wdl
+    pub wins_l: u32,
+    pub draws: u32,
+    pub wins_r: u32,
     pub win_rate_l: f32,
     pub draw_rate: f32,
     pub win_rate_r: f32,
 
     //elo of the left player, assuming the right elo is 0
     pub elo_l: f32,
+    //half the width of the 95% confidence interval of elo_l
+    pub elo_error_l: f32,
+    //likelihood of superiority, the probability that the left player is stronger
+    pub los_l: f32,
 
     //time per move in seconds
     pub time_l: f32,
     pub time_r: f32,
+    //distribution of the time per move, to spot rare slow moves the average hides
+    pub move_times_l: MoveTimes,
+    pub move_times_r: MoveTimes,
+
+    //games lost on time
+    pub time_forfeits_l: u32,
+    pub time_forfeits_r: u32,
+
+    //games ended early by adjudication
+    pub adjudicated: u32,
+
+    //games aborted by a panic, not included in the other statistics
+    pub panics: Vec<PanickedGame>,
+
+    //the number of game pairs where the left player scored 0 to 4 half points, only for both_sides matches.
+    // pairs where a game panicked are not included
+    pub pairs: Option<[u32; 5]>,
 
     // bot debug strings
     pub debug_l: String,
     pub debug_r: String,
+
+    // the sequential test, only for matches played by run_sprt
+    pub sprt: Option<SprtResult>,
+}
+
+impl BotGameResult {
+    /// How significant the difference in strength is, treating every game as independent.
+    pub fn trinomial_significance(&self) -> Significance {
+        trinomial_significance(self.wins_l, self.draws, self.wins_r)
+    }
+
+    /// How significant the difference in strength is based on the game pairs, only for both_sides matches.
+    pub fn pentanomial_significance(&self) -> Option<Significance> {
+        self.pairs.map(pentanomial_significance)
+    }
+
+    /// The column names of [to_csv_row](BotGameResult::to_csv_row).
+    pub const CSV_HEADER: &'static str = "bot_l,bot_r,game_count,game_length,wins_l,draws,wins_r,\
+        win_rate_l,draw_rate,win_rate_r,elo_l,elo_error_l,los_l,time_l,time_r,time_forfeits_l,time_forfeits_r,\
+        adjudicated,panics";
+
+    /// All statistics as a JSON object with the fields of this struct.
+    pub fn to_json(&self) -> Value {
+        let panics = self
+            .panics
+            .iter()
+            .map(|p| {
+                json!({
+                    "game_id": p.game_id,
+                    "left_first": p.left_first,
+                    "message": p.message,
+                    "board": p.board,
+                    "moves": p.moves,
+                })
+            })
+            .collect_vec();
+        let sprt = self.sprt.as_ref().map(|sprt| {
+            json!({
+                "elo0": sprt.sprt.elo0,
+                "elo1": sprt.sprt.elo1,
+                "alpha": sprt.sprt.alpha,
+                "beta": sprt.sprt.beta,
+                "accepted": sprt.accepted.map(|h| format!("{:?}", h)),
+                "llr": sprt.llr,
+            })
+        });
+
+        json!({
+            "bot_l": self.debug_l,
+            "bot_r": self.debug_r,
+            "game_count": self.game_count,
+            "game_length": self.game_length,
+            "wins_l": self.wins_l,
+            "draws": self.draws,
+            "wins_r": self.wins_r,
+            "win_rate_l": self.win_rate_l,
+            "draw_rate": self.draw_rate,
+            "win_rate_r": self.win_rate_r,
+            "elo_l": self.elo_l,
+            "elo_error_l": self.elo_error_l,
+            "los_l": self.los_l,
+            "time_l": self.time_l,
+            "time_r": self.time_r,
+            "move_times_l": {"p50": self.move_times_l.p50, "p95": self.move_times_l.p95, "max": self.move_times_l.max},
+            "move_times_r": {"p50": self.move_times_r.p50, "p95": self.move_times_r.p95, "max": self.move_times_r.max},
+            "time_forfeits_l": self.time_forfeits_l,
+            "time_forfeits_r": self.time_forfeits_r,
+            "adjudicated": self.adjudicated,
+            "panics": panics,
+            "pairs": self.pairs,
+            "p_value": self.trinomial_significance().p_value,
+            "pair_p_value": self.pentanomial_significance().map(|s| s.p_value),
+            "sprt": sprt,
+        })
+    }
+
+    /// The statistics as a line of CSV, without a trailing newline. The columns are given by
+    /// [CSV_HEADER](BotGameResult::CSV_HEADER), panicked games are only counted.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            csv_quote(&self.debug_l),
+            csv_quote(&self.debug_r),
+            self.game_count,
+            self.game_length,
+            self.wins_l,
+            self.draws,
+            self.wins_r,
+            self.win_rate_l,
+            self.draw_rate,
+            self.win_rate_r,
+            self.elo_l,
+            self.elo_error_l,
+            self.los_l,
+            self.time_l,
+            self.time_r,
+            self.time_forfeits_l,
+            self.time_forfeits_r,
+            self.adjudicated,
+            self.panics.len(),
+        )
+    }
+}
+
+/// Quote a CSV field, doubling the quotes inside it.
+fn csv_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Write `status` to `path` as JSON, replacing the file atomically so readers never see a partial file.
+pub fn write_status(path: &Path, status: &Value) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(status)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
 
 fn debug_to_string(d: &impl Debug) -> String {
@@ -0,0 +1,332 @@
+//! Elo rating calculations, used to summarize the results of matches between bots.
+//!
+//! See <https://www.chessprogramming.org/Match_Statistics> for the formulas.
+
+/// The Elo difference corresponding to an expected `score` in `0..=1`, where a draw counts as half a win.
+pub fn elo_from_score(score: f32) -> f32 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// The expected score in `0..=1` for an Elo difference of `elo`, the inverse of [elo_from_score].
+pub fn score_from_elo(elo: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf(-elo / 400.0))
+}
+
+/// An Elo difference estimated from a number of games.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EloEstimate {
+    /// The estimated Elo difference.
+    pub elo: f32,
+    /// Half the width of the 95% confidence interval around `elo`, infinite if the interval is unbounded.
+    pub error: f32,
+    /// The likelihood of superiority, the probability that the Elo difference is positive.
+    pub los: f32,
+}
+
+/// Estimate the Elo difference of a player from its `wins`, `draws` and `losses`.
+/// Without any games the estimate is `0` with an infinite error.
+pub fn elo_estimate(wins: u32, draws: u32, losses: u32) -> EloEstimate {
+    if wins + draws + losses == 0 {
+        return EloEstimate {
+            elo: 0.0,
+            error: f32::INFINITY,
+            los: 0.5,
+        };
+    }
+
+    let n = (wins + draws + losses) as f32;
+    let (w, d, l) = (wins as f32 / n, draws as f32 / n, losses as f32 / n);
+
+    let score = w + 0.5 * d;
+    let variance = w * (1.0 - score).powi(2) + d * (0.5 - score).powi(2) + l * (0.0 - score).powi(2);
+    let std_error = (variance / n).sqrt();
+
+    // 1.96 standard deviations on both sides cover 95% of a normal distribution
+    let low = elo_from_score((score - 1.96 * std_error).max(0.0));
+    let high = elo_from_score((score + 1.96 * std_error).min(1.0));
+    let error = if low.is_finite() && high.is_finite() {
+        (high - low) / 2.0
+    } else {
+        f32::INFINITY
+    };
+
+    EloEstimate {
+        elo: elo_from_score(score),
+        error,
+        los: likelihood_of_superiority(wins, losses),
+    }
+}
+
+/// The probability that a player with `wins` and `losses` is the stronger one, draws don't matter.
+pub fn likelihood_of_superiority(wins: u32, losses: u32) -> f32 {
+    if wins + losses == 0 {
+        return 0.5;
+    }
+    let (wins, losses) = (wins as f64, losses as f64);
+    (0.5 * (1.0 + erf((wins - losses) / (2.0 * (wins + losses)).sqrt()))) as f32
+}
+
+/// The error function, with a maximum error of `1.2e-7`.
+/// See Numerical Recipes, section 6.2.
+pub(crate) fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let poly = -x * x - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let erfc = t * poly.exp();
+
+    if x >= 0.0 {
+        1.0 - erfc
+    } else {
+        erfc - 1.0
+    }
+}
+
+/// How significant the result of a match is, compared to the null hypothesis that both players are equally strong.
+///
+/// The score is assumed to be normally distributed around its mean, which is accurate for more than a few dozen games.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Significance {
+    /// The average score in `0..=1`.
+    pub score: f32,
+    /// The number of standard errors the score is away from `0.5`.
+    pub z: f32,
+    /// The two-sided p-value, the probability of a score at least this far from `0.5` between equal players.
+    pub p_value: f32,
+    /// The likelihood of superiority, the probability that the player is the stronger one.
+    pub los: f32,
+}
+
+impl Significance {
+    /// `counts[i]` is the number of samples with score `values[i]`, where the values are in `0..=1`.
+    fn from_distribution(values: &[f64], counts: &[u32]) -> Self {
+        let n = counts.iter().sum::<u32>() as f64;
+        if n == 0.0 {
+            return Significance {
+                score: 0.5,
+                z: 0.0,
+                p_value: 1.0,
+                los: 0.5,
+            };
+        }
+
+        let score = values.iter().zip(counts).map(|(&v, &c)| v * c as f64).sum::<f64>() / n;
+        let variance = values
+            .iter()
+            .zip(counts)
+            .map(|(&v, &c)| c as f64 * (v - score).powi(2))
+            .sum::<f64>()
+            / n;
+        let std_error = (variance / n).sqrt();
+
+        let z = if std_error == 0.0 {
+            if score == 0.5 {
+                0.0
+            } else {
+                (score - 0.5).signum() * f64::INFINITY
+            }
+        } else {
+            (score - 0.5) / std_error
+        };
+        let normal_cdf = |x: f64| 0.5 * (1.0 + erf(x / 2f64.sqrt()));
+
+        Significance {
+            score: score as f32,
+            z: z as f32,
+            p_value: (2.0 * (1.0 - normal_cdf(z.abs()))) as f32,
+            los: normal_cdf(z) as f32,
+        }
+    }
+}
+
+/// The [Significance] of `wins`, `draws` and `losses`, treating every game as an independent sample.
+pub fn trinomial_significance(wins: u32, draws: u32, losses: u32) -> Significance {
+    Significance::from_distribution(&[1.0, 0.5, 0.0], &[wins, draws, losses])
+}
+
+/// The [Significance] of the results of game pairs, where both players play each opening once with either side.
+/// `pairs[i]` is the number of pairs where the player scored `i` half points in total, so `pairs[4]` counts
+/// the pairs where it won both games.
+///
+/// Pairs cancel out most of the advantage of the side that moves first or an unbalanced opening,
+/// which makes this more accurate than [trinomial_significance] for paired games.
+pub fn pentanomial_significance(pairs: [u32; 5]) -> Significance {
+    Significance::from_distribution(&[0.0, 0.25, 0.5, 0.75, 1.0], &pairs)
+}
+
+/// A sequential probability ratio test between the hypotheses that the Elo difference is `elo0` (H0) or `elo1` (H1),
+/// with false positive rate `alpha` and false negative rate `beta`.
+///
+/// The log-likelihood ratio is computed with the normal approximation of the score distribution,
+/// the same model used by fishtest. The result frequencies include [SPRT_PRIOR_DRAWS] virtual draws, otherwise the
+/// variance is zero when one side won every game and the test could never stop.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprt {
+    pub elo0: f32,
+    pub elo1: f32,
+    pub alpha: f32,
+    pub beta: f32,
+}
+
+/// The number of virtual draws added to the results by [Sprt::llr]. It only has to keep the variance positive,
+/// so it is kept small to barely move the score of long matches.
+pub const SPRT_PRIOR_DRAWS: f32 = 2.0;
+
+/// A hypothesis accepted by a [Sprt].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Hypothesis {
+    H0,
+    H1,
+}
+
+impl Sprt {
+    pub fn new(elo0: f32, elo1: f32, alpha: f32, beta: f32) -> Self {
+        assert!(elo0 < elo1, "elo0 must be smaller than elo1");
+        assert!(0.0 < alpha && alpha < 1.0 && 0.0 < beta && beta < 1.0);
+        Sprt {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        }
+    }
+
+    /// The lower and upper bound of the log-likelihood ratio, H0 is accepted below the lower one
+    /// and H1 above the upper one.
+    pub fn bounds(&self) -> (f32, f32) {
+        let lower = (self.beta / (1.0 - self.alpha)).ln();
+        let upper = ((1.0 - self.beta) / self.alpha).ln();
+        (lower, upper)
+    }
+
+    /// The log-likelihood ratio of H1 versus H0 for the given results.
+    pub fn llr(&self, wins: u32, draws: u32, losses: u32) -> f32 {
+        let n = (wins + draws + losses) as f32;
+        if n == 0.0 {
+            return 0.0;
+        }
+        let total = n + SPRT_PRIOR_DRAWS;
+        let (w, d, l) = (
+            wins as f32 / total,
+            (draws as f32 + SPRT_PRIOR_DRAWS) / total,
+            losses as f32 / total,
+        );
+
+        let score = w + 0.5 * d;
+        let variance = w * (1.0 - score).powi(2) + d * (0.5 - score).powi(2) + l * (0.0 - score).powi(2);
+        if variance == 0.0 {
+            // only draws
+            return 0.0;
+        }
+
+        let score0 = score_from_elo(self.elo0);
+        let score1 = score_from_elo(self.elo1);
+        n * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// The hypothesis accepted at log-likelihood ratio `llr`, if any.
+    pub fn decide(&self, llr: f32) -> Option<Hypothesis> {
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            Some(Hypothesis::H0)
+        } else if llr >= upper {
+            Some(Hypothesis::H1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The number of virtual draws added to every pairing by [fit_elo], which keeps ratings finite when a player won or
+/// lost all of its games. This is the same prior BayesElo uses by default.
+pub const PRIOR_DRAWS: f32 = 2.0;
+
+/// Fit Elo ratings to the results of a tournament with the Bradley-Terry model, where draws count as half a win.
+///
+/// `points[i][j]` is the score of player `i` against player `j` and `games[i][j]` the number of games they played.
+/// The ratings are the maximum a posteriori estimate with a prior of [PRIOR_DRAWS] virtual draws per pairing,
+/// shifted so their average is zero.
+pub fn fit_elo(points: &[Vec<f32>], games: &[Vec<u32>]) -> Vec<f32> {
+    let n = points.len();
+    assert_eq!(games.len(), n);
+
+    let games_with_prior = |i: usize, j: usize| {
+        if i == j || games[i][j] == 0 {
+            0.0
+        } else {
+            games[i][j] as f32 + PRIOR_DRAWS
+        }
+    };
+    let total_points = (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|&j| games_with_prior(i, j) > 0.0)
+                .map(|j| points[i][j] + PRIOR_DRAWS / 2.0)
+                .sum::<f32>()
+        })
+        .collect::<Vec<f32>>();
+
+    // minorization-maximization, see "MM algorithms for generalized Bradley-Terry models" by Hunter
+    let mut gamma = vec![1.0f32; n];
+    for _ in 0..10_000 {
+        let mut next = (0..n)
+            .map(|i| {
+                let denominator: f32 = (0..n).map(|j| games_with_prior(i, j) / (gamma[i] + gamma[j])).sum();
+                if denominator == 0.0 {
+                    1.0
+                } else {
+                    total_points[i] / denominator
+                }
+            })
+            .collect::<Vec<f32>>();
+
+        let mean_log = next.iter().map(|g| g.ln()).sum::<f32>() / n as f32;
+        next.iter_mut().for_each(|g| *g /= mean_log.exp());
+
+        let change = gamma.iter().zip(&next).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        gamma = next;
+        if change < 1e-6 {
+            break;
+        }
+    }
+
+    gamma.iter().map(|g| 400.0 * g.log10()).collect()
+}
+
+/// Fit the Elo rating of a single player against opponents with known ratings `anchors`,
+/// for example the reference opponents of a gauntlet.
+///
+/// `points[i]` is the score of the player against opponent `i` and `games[i]` the number of games they played.
+/// The same prior of [PRIOR_DRAWS] virtual draws per opponent as in [fit_elo] is used.
+pub fn fit_elo_anchored(anchors: &[f32], points: &[f32], games: &[u32]) -> f32 {
+    assert!(anchors.len() == points.len() && anchors.len() == games.len());
+
+    let played = (0..anchors.len()).filter(|&i| games[i] > 0).collect::<Vec<_>>();
+    if played.is_empty() {
+        return 0.0;
+    }
+    let total_points: f32 = played.iter().map(|&i| points[i] + PRIOR_DRAWS / 2.0).sum();
+    let expected_points = |elo: f32| -> f32 {
+        played
+            .iter()
+            .map(|&i| (games[i] as f32 + PRIOR_DRAWS) * score_from_elo(elo - anchors[i]))
+            .sum()
+    };
+
+    // the expected score increases with the rating, so bisection finds the maximum likelihood rating
+    let mut low = played.iter().map(|&i| anchors[i]).fold(f32::INFINITY, f32::min) - 4000.0;
+    let mut high = played.iter().map(|&i| anchors[i]).fold(f32::NEG_INFINITY, f32::max) + 4000.0;
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if expected_points(mid) < total_points {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
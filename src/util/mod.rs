@@ -1,6 +1,23 @@
 //! Various utility functions.
+pub mod annotate;
+pub mod arena;
+pub mod bitboard;
 pub mod board_gen;
+#[cfg(feature = "native")]
 pub mod bot_game;
+pub mod elo;
 pub mod game_stats;
+pub mod pgn;
+pub mod rating;
+pub mod render;
+pub mod replay;
+pub mod sgf;
+#[cfg(feature = "native")]
+pub mod spectator;
+#[cfg(feature = "native")]
+pub mod tournament;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod zobrist;
 
 pub mod bits;
@@ -0,0 +1,361 @@
+//! Reading and writing games in the [PGN](https://en.wikipedia.org/wiki/Portable_Game_Notation) format.
+//!
+//! The format itself is game-agnostic here, moves are stored as strings and the conversion from and to
+//! actual moves is supplied by the caller. For chess use [move_to_san](crate::games::chess::move_to_san) and
+//! [move_from_san](crate::games::chess::move_from_san), for ataxx the UAI move notation is the usual choice.
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::board::{Board, Outcome, Player};
+
+/// A single game, consisting of tag pairs, moves and the result.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    /// The comment after each move, if any. Has the same length as `moves`.
+    pub comments: Vec<Option<String>>,
+    /// The result of the game, `None` if the game is unfinished or the result is unknown.
+    pub result: Option<Outcome>,
+    /// The player that plays the first move.
+    pub start_player: Player,
+}
+
+impl PgnGame {
+    /// Record the game played from `start` with `moves`, using `notation` to convert each move to a string.
+    /// The result and the `Result` tag are derived from the final board.
+    pub fn from_game<B: Board>(
+        start: &B,
+        moves: &[B::Move],
+        tags: Vec<(String, String)>,
+        mut notation: impl FnMut(&B, B::Move) -> String,
+    ) -> Self {
+        let mut board = start.clone();
+        let mut move_strs = vec![];
+        for &mv in moves {
+            move_strs.push(notation(&board, mv));
+            board.play(mv);
+        }
+
+        let mut game = PgnGame {
+            tags,
+            comments: vec![None; move_strs.len()],
+            moves: move_strs,
+            result: board.outcome(),
+            start_player: start.next_player(),
+        };
+        game.set_tag("Result", result_to_str(game.result));
+        game
+    }
+
+    /// Get the value of the tag `key`, if it exists.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Set the tag `key` to `value`, replacing the previous value if any.
+    pub fn set_tag(&mut self, key: &str, value: &str) {
+        match self.tags.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.tags.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// Convert the moves of this game back to actual moves, starting from `start`.
+    /// `parse` should return `None` if the string is not a valid move on the given board.
+    pub fn replay<B: Board>(
+        &self,
+        start: &B,
+        mut parse: impl FnMut(&B, &str) -> Option<B::Move>,
+    ) -> Result<Vec<B::Move>, String> {
+        let mut board = start.clone();
+        let mut moves = vec![];
+
+        for (i, mv_str) in self.moves.iter().enumerate() {
+            if board.is_done() {
+                return Err(format!("Move {} '{}' played after the game was done", i, mv_str));
+            }
+            let mv = parse(&board, mv_str)
+                .filter(|&mv| board.is_available_move(mv))
+                .ok_or_else(|| format!("Move {} '{}' is not valid on board {:?}", i, mv_str, board))?;
+
+            board.play(mv);
+            moves.push(mv);
+        }
+
+        Ok(moves)
+    }
+}
+
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", key, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = vec![];
+        let mut player = self.start_player;
+        let mut move_number = 1;
+        for (i, mv) in self.moves.iter().enumerate() {
+            if player == Player::A {
+                tokens.push(format!("{}.", move_number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", move_number));
+            }
+            tokens.push(mv.clone());
+            if let Some(comment) = &self.comments[i] {
+                // comments can't be escaped, so replace the closing brace
+                tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+            }
+
+            if player == Player::B {
+                move_number += 1;
+            }
+            player = player.other();
+        }
+        tokens.push(result_to_str(self.result).to_string());
+
+        // keep lines shorter than 80 characters, as recommended by the standard
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length != 0 {
+                if line_length + 1 + token.len() >= 80 {
+                    writeln!(f)?;
+                    line_length = 0;
+                } else {
+                    write!(f, " ")?;
+                    line_length += 1;
+                }
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// Parse all games in `input`.
+///
+/// The parser is tolerant: line comments, variations, annotation glyphs and move numbers are skipped,
+/// and a missing result token falls back to the `Result` tag. Brace comments are attached to the preceding move.
+pub fn parse_pgn(input: &str) -> Result<Vec<PgnGame>, String> {
+    let mut games = vec![];
+    let mut builder = GameBuilder::default();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '[' => {
+                if builder.in_movetext() {
+                    games.push(builder.finish());
+                }
+                let content = take_until(&mut chars, ']')?;
+                builder.tags.push(parse_tag(&content)?);
+            }
+            '{' => {
+                let comment = take_until(&mut chars, '}')?;
+                builder.push_comment(comment.trim());
+            }
+            ';' | '%' => {
+                // line comment or escape line, the latter is only valid at the start of a line but accept it anyway
+                let _ = take_until(&mut chars, '\n');
+            }
+            '(' => skip_variation(&mut chars)?,
+            ')' => return Err("Unmatched ')'".to_string()),
+            '$' => {
+                while matches!(chars.peek(), Some(c) if c.is_ascii_digit()) {
+                    chars.next();
+                }
+            }
+            _ => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "[]{}();$".contains(c) {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+
+                if let Some(result) = result_from_str(&token) {
+                    builder.result = Some(result);
+                    games.push(builder.finish());
+                } else {
+                    builder.push_move_token(&token);
+                }
+            }
+        }
+    }
+
+    if builder.in_movetext() || !builder.tags.is_empty() {
+        games.push(builder.finish());
+    }
+
+    Ok(games)
+}
+
+#[derive(Default)]
+struct GameBuilder {
+    tags: Vec<(String, String)>,
+    moves: Vec<String>,
+    comments: Vec<Option<String>>,
+    result: Option<Option<Outcome>>,
+    start_player_b: bool,
+}
+
+impl GameBuilder {
+    fn in_movetext(&self) -> bool {
+        !self.moves.is_empty() || self.result.is_some()
+    }
+
+    fn push_move_token(&mut self, token: &str) {
+        // strip the move number, eg. "12." or "12..." or "12.e4"
+        let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let token = if digits > 0 && token[digits..].starts_with('.') {
+            let rest = token[digits..].trim_start_matches('.');
+            if self.moves.is_empty() && token[digits..].starts_with("...") {
+                self.start_player_b = true;
+            }
+            rest
+        } else {
+            token
+        };
+
+        // strip move suffix annotations like "!" or "?!"
+        let token = token.trim_end_matches(&['!', '?'][..]);
+
+        if !token.is_empty() {
+            self.moves.push(token.to_string());
+            self.comments.push(None);
+        }
+    }
+
+    /// Attach a comment to the last move, comments before the first move are dropped.
+    fn push_comment(&mut self, comment: &str) {
+        if let Some(last) = self.comments.last_mut() {
+            match last {
+                Some(existing) => {
+                    existing.push(' ');
+                    existing.push_str(comment);
+                }
+                None => *last = Some(comment.to_string()),
+            }
+        }
+    }
+
+    fn finish(&mut self) -> PgnGame {
+        let builder = std::mem::take(self);
+
+        let result = match builder.result {
+            Some(result) => result,
+            None => builder
+                .tags
+                .iter()
+                .find(|(k, _)| k == "Result")
+                .and_then(|(_, v)| result_from_str(v))
+                .flatten(),
+        };
+
+        PgnGame {
+            tags: builder.tags,
+            moves: builder.moves,
+            comments: builder.comments,
+            result,
+            start_player: if builder.start_player_b { Player::B } else { Player::A },
+        }
+    }
+}
+
+fn take_until(chars: &mut Peekable<Chars>, end: char) -> Result<String, String> {
+    let mut result = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in chars {
+        if c == end && !in_string {
+            return Ok(result);
+        }
+        if end == ']' {
+            // tag values are strings that can contain ']' and escaped quotes
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = !in_string;
+            }
+        }
+        result.push(c);
+    }
+
+    Err(format!("Missing closing '{}'", end))
+}
+
+fn skip_variation(chars: &mut Peekable<Chars>) -> Result<(), String> {
+    let mut depth = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            '{' => {
+                take_until(chars, '}')?;
+            }
+            _ => {}
+        }
+    }
+    Err("Missing closing ')'".to_string())
+}
+
+fn parse_tag(content: &str) -> Result<(String, String), String> {
+    let content = content.trim();
+    let (key, value) = content
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("Invalid tag '{}'", content))?;
+
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| format!("Invalid tag value '{}'", value))?;
+
+    let mut unescaped = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if !escaped && c == '\\' {
+            escaped = true;
+        } else {
+            unescaped.push(c);
+            escaped = false;
+        }
+    }
+
+    Ok((key.to_string(), unescaped))
+}
+
+fn result_to_str(result: Option<Outcome>) -> &'static str {
+    match result {
+        Some(Outcome::WonBy(Player::A)) => "1-0",
+        Some(Outcome::WonBy(Player::B)) => "0-1",
+        Some(Outcome::Draw) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+/// Returns `Some(None)` for the unknown result `*`.
+fn result_from_str(s: &str) -> Option<Option<Outcome>> {
+    match s {
+        "1-0" => Some(Some(Outcome::WonBy(Player::A))),
+        "0-1" => Some(Some(Outcome::WonBy(Player::B))),
+        "1/2-1/2" => Some(Some(Outcome::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
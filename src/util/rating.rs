@@ -0,0 +1,352 @@
+//! Ratings that are updated after every game, to maintain a ladder of bots across many matches.
+//!
+//! Unlike [elo](crate::util::elo), which estimates the strength difference from the results of a single match,
+//! these ratings are updated incrementally from a stream of results and can be stored between runs in a [Ladder].
+//! [Glicko2] follows <http://www.glicko.net/glicko/glicko2.pdf>, [elo_update] is the classic Elo update.
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use serde_json::{json, Value};
+
+/// The rating every new player starts with.
+pub const START_RATING: f64 = 1500.0;
+
+/// The conversion factor between the Glicko and the Glicko-2 scale.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The convergence tolerance of the volatility iteration.
+const VOLATILITY_EPSILON: f64 = 1e-6;
+
+/// Update the Elo ratings of two players after a game where player `a` got `score_a` in `0..=1`,
+/// with a maximum change of `k`. Returns the new ratings of `a` and `b`.
+pub fn elo_update(rating_a: f64, rating_b: f64, score_a: f64, k: f64) -> (f64, f64) {
+    let expected_a = 1.0 / (1.0 + 10f64.powf((rating_b - rating_a) / 400.0));
+    let delta = k * (score_a - expected_a);
+    (rating_a + delta, rating_b - delta)
+}
+
+/// A Glicko-2 rating, on the same scale as Elo ratings.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    /// The uncertainty of the rating, which decreases with the number of games played.
+    pub deviation: f64,
+    /// How much the strength of the player is expected to fluctuate.
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Glicko2Rating {
+            rating: START_RATING,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Glicko2Rating {
+    /// The 95% confidence interval of the rating.
+    pub fn interval(&self) -> (f64, f64) {
+        (self.rating - 1.96 * self.deviation, self.rating + 1.96 * self.deviation)
+    }
+}
+
+/// The Glicko-2 rating system, where `tau` constrains the change in volatility over time.
+/// Reasonable values are between `0.3` and `1.2`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Glicko2 {
+    pub tau: f64,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Glicko2 { tau: 0.5 }
+    }
+}
+
+impl Glicko2 {
+    pub fn new(tau: f64) -> Self {
+        assert!(tau > 0.0, "tau must be positive");
+        Glicko2 { tau }
+    }
+
+    /// The rating of `player` after a rating period with `results`, pairs of the rating an opponent had at the start
+    /// of the period and the score in `0..=1` against them. Without results only the deviation increases.
+    pub fn update(&self, player: Glicko2Rating, results: &[(Glicko2Rating, f64)]) -> Glicko2Rating {
+        let mu = (player.rating - START_RATING) / GLICKO2_SCALE;
+        let phi = player.deviation / GLICKO2_SCALE;
+        let sigma = player.volatility;
+
+        if results.is_empty() {
+            return Glicko2Rating {
+                deviation: (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE,
+                ..player
+            };
+        }
+
+        // the estimated variance of the rating based only on the results, and the estimated improvement
+        let mut v_inv = 0.0;
+        let mut improvement = 0.0;
+        for &(opponent, score) in results {
+            let mu_j = (opponent.rating - START_RATING) / GLICKO2_SCALE;
+            let g = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - mu_j)).exp());
+            v_inv += g * g * expected * (1.0 - expected);
+            improvement += g * (score - expected);
+        }
+        let v = 1.0 / v_inv;
+        let delta = v * improvement;
+
+        let sigma = self.new_volatility(phi, sigma, v, delta);
+        let phi_star = (phi * phi + sigma * sigma).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+
+        Glicko2Rating {
+            rating: mu * GLICKO2_SCALE + START_RATING,
+            deviation: phi * GLICKO2_SCALE,
+            volatility: sigma,
+        }
+    }
+
+    /// Step 5 of the paper, the Illinois algorithm to find the new volatility.
+    fn new_volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let tau = self.tau;
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denominator = 2.0 * (phi * phi + v + ex).powi(2);
+            ex * (delta * delta - phi * phi - v - ex) / denominator - (x - a) / (tau * tau)
+        };
+
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > VOLATILITY_EPSILON {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+
+        (big_a / 2.0).exp()
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+/// The ratings of a single player in a [Ladder].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LadderEntry {
+    pub glicko: Glicko2Rating,
+    pub elo: f64,
+    pub games: u32,
+}
+
+impl Default for LadderEntry {
+    fn default() -> Self {
+        LadderEntry {
+            glicko: Glicko2Rating::default(),
+            elo: START_RATING,
+            games: 0,
+        }
+    }
+}
+
+/// A persistent ladder of players, for example bots or networks, rated by both Glicko-2 and Elo.
+///
+/// Elo ratings are updated after every result, Glicko-2 ratings at the end of every rating period,
+/// see [end_period](Ladder::end_period). The ladder can be stored with [to_json](Ladder::to_json) and loaded again
+/// with [from_json](Ladder::from_json), including the results of the current rating period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ladder {
+    glicko: Glicko2,
+    k: f64,
+    players: BTreeMap<String, LadderEntry>,
+    // results in the current rating period: player a, player b and the score of a
+    pending: Vec<(String, String, f64)>,
+}
+
+impl Ladder {
+    /// Create an empty ladder, where `k` is the maximum change of the Elo ratings per game.
+    pub fn new(glicko: Glicko2, k: f64) -> Self {
+        Ladder {
+            glicko,
+            k,
+            players: BTreeMap::new(),
+            pending: vec![],
+        }
+    }
+
+    /// Add a player with the starting ratings, if it's not on the ladder yet.
+    pub fn add_player(&mut self, name: &str) {
+        self.players.entry(name.to_string()).or_default();
+    }
+
+    /// Record a game between `a` and `b` where `a` got `score_a` in `0..=1`, players are added if necessary.
+    pub fn add_result(&mut self, a: &str, b: &str, score_a: f64) {
+        assert!((0.0..=1.0).contains(&score_a), "Score must be between 0 and 1");
+        assert_ne!(a, b, "A player can't play against itself");
+        self.add_player(a);
+        self.add_player(b);
+
+        let (elo_a, elo_b) = elo_update(self.players[a].elo, self.players[b].elo, score_a, self.k);
+        let entry_a = self.players.get_mut(a).unwrap();
+        entry_a.elo = elo_a;
+        entry_a.games += 1;
+        let entry_b = self.players.get_mut(b).unwrap();
+        entry_b.elo = elo_b;
+        entry_b.games += 1;
+
+        self.pending.push((a.to_string(), b.to_string(), score_a));
+    }
+
+    /// Record the results of a match between `a` and `b`, in the order wins, draws and losses of `a`.
+    pub fn add_results(&mut self, a: &str, b: &str, wins: u32, draws: u32, losses: u32) {
+        let scores = [(wins, 1.0), (draws, 0.5), (losses, 0.0)];
+        for &(count, score) in &scores {
+            for _ in 0..count {
+                self.add_result(a, b, score);
+            }
+        }
+    }
+
+    /// End the current rating period, updating the Glicko-2 ratings of all players at once.
+    /// The deviation of players that didn't play in this period increases.
+    pub fn end_period(&mut self) {
+        let mut results: BTreeMap<&str, Vec<(Glicko2Rating, f64)>> = BTreeMap::new();
+        for (a, b, score_a) in &self.pending {
+            results.entry(a).or_default().push((self.players[b].glicko, *score_a));
+            results
+                .entry(b)
+                .or_default()
+                .push((self.players[a].glicko, 1.0 - score_a));
+        }
+
+        let updated = self
+            .players
+            .iter()
+            .map(|(name, entry)| {
+                let player_results = results.get(&**name).map_or(&[][..], |r| &r[..]);
+                (name.clone(), self.glicko.update(entry.glicko, player_results))
+            })
+            .collect::<Vec<_>>();
+
+        for (name, glicko) in updated {
+            self.players.get_mut(&name).unwrap().glicko = glicko;
+        }
+        self.pending.clear();
+    }
+
+    /// The ratings of player `name`, if it's on the ladder.
+    pub fn get(&self, name: &str) -> Option<&LadderEntry> {
+        self.players.get(name)
+    }
+
+    /// All players, sorted from the highest to the lowest Glicko-2 rating.
+    pub fn ranking(&self) -> Vec<(&str, &LadderEntry)> {
+        let mut ranking = self
+            .players
+            .iter()
+            .map(|(name, entry)| (&**name, entry))
+            .collect::<Vec<_>>();
+        ranking.sort_by(|a, b| b.1.glicko.rating.partial_cmp(&a.1.glicko.rating).unwrap());
+        ranking
+    }
+
+    /// The number of results in the current rating period.
+    pub fn pending_results(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The ladder as a JSON object, which can be loaded again with [from_json](Ladder::from_json).
+    pub fn to_json(&self) -> Value {
+        let players = self
+            .players
+            .iter()
+            .map(|(name, entry)| {
+                let value = json!({
+                    "rating": entry.glicko.rating,
+                    "deviation": entry.glicko.deviation,
+                    "volatility": entry.glicko.volatility,
+                    "elo": entry.elo,
+                    "games": entry.games,
+                });
+                (name.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "tau": self.glicko.tau,
+            "k": self.k,
+            "players": players,
+            "pending": self.pending,
+        })
+    }
+
+    /// Load a ladder stored with [to_json](Ladder::to_json).
+    pub fn from_json(value: &Value) -> Result<Ladder, String> {
+        let float = |value: &Value, key: &str| {
+            value[key]
+                .as_f64()
+                .ok_or_else(|| format!("Missing or invalid field '{}'", key))
+        };
+
+        let mut ladder = Ladder::new(Glicko2::new(float(value, "tau")?), float(value, "k")?);
+
+        let players = value["players"]
+            .as_object()
+            .ok_or_else(|| "Missing or invalid field 'players'".to_string())?;
+        for (name, entry) in players {
+            let games = entry["games"]
+                .as_u64()
+                .ok_or_else(|| format!("Missing or invalid field 'games' for player '{}'", name))?;
+            let entry = LadderEntry {
+                glicko: Glicko2Rating {
+                    rating: float(entry, "rating")?,
+                    deviation: float(entry, "deviation")?,
+                    volatility: float(entry, "volatility")?,
+                },
+                elo: float(entry, "elo")?,
+                games: games as u32,
+            };
+            ladder.players.insert(name.clone(), entry);
+        }
+
+        let pending = value["pending"]
+            .as_array()
+            .ok_or_else(|| "Missing or invalid field 'pending'".to_string())?;
+        for result in pending {
+            let parsed = match result.as_array().map(|r| &r[..]) {
+                Some([a, b, score]) => a.as_str().zip(b.as_str()).zip(score.as_f64()),
+                _ => None,
+            };
+            let ((a, b), score) = parsed.ok_or_else(|| format!("Invalid pending result {}", result))?;
+            if !ladder.players.contains_key(a) || !ladder.players.contains_key(b) {
+                return Err(format!("Pending result {} refers to an unknown player", result));
+            }
+            ladder.pending.push((a.to_string(), b.to_string(), score));
+        }
+
+        Ok(ladder)
+    }
+}
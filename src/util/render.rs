@@ -0,0 +1,248 @@
+//! Draw boards as SVG images, for documentation and analysis tooling.
+//!
+//! Any board that implements [RenderBoard] can be drawn, which includes all boards in [games](crate::games)
+//! except the dummy game. The last move can be highlighted and a heatmap, for example the policy or
+//! visit distribution of a search, can be drawn on top of the board. Only SVG is supported,
+//! PNG images can be created from it with any SVG rasterizer.
+//!
+//! To quickly see why a bot played a move, [to_terminal] draws the [visit_heatmap] of its search as a colored grid.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::ai::mcts::Tree;
+use crate::board::{Board, Player};
+
+/// The size of a single cell in pixels.
+const CELL_SIZE: u32 = 40;
+
+/// What is drawn in a single cell of the board.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Cell {
+    Empty,
+    /// A cell that can't be played on, for example an ataxx gap.
+    Blocked,
+    /// A stone of the given player, drawn as a disc.
+    Stone(Player),
+    /// A piece drawn as the given symbol, which should also show the player it belongs to.
+    Piece(char),
+}
+
+/// A board that can be drawn as a grid of cells.
+pub trait RenderBoard: Board {
+    /// The number of columns and rows.
+    fn render_size(&self) -> (u8, u8);
+
+    /// The content of the cell at column `x` and row `y`, starting from the top left.
+    fn render_cell(&self, x: u8, y: u8) -> Cell;
+
+    /// The cells `mv` touches, ending with the cell the move is played on. Can be empty for moves like passes.
+    fn render_move_cells(&self, mv: Self::Move) -> Vec<(u8, u8)>;
+
+    /// If `Some(n)`, thicker lines are drawn every `n` cells, for boards that consist of sub-boards.
+    fn render_block_size(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// Draw `board` as an SVG image.
+///
+/// The cells of `last_move` are highlighted. `heatmap` assigns a weight to moves, the cell each move is played on is
+/// colored with an intensity relative to the largest weight. The weights of moves played on the same cell are summed.
+pub fn to_svg<B: RenderBoard>(board: &B, last_move: Option<B::Move>, heatmap: &[(B::Move, f32)]) -> String {
+    let (width, height) = board.render_size();
+    let cell = CELL_SIZE;
+    let mut s = String::new();
+
+    writeln!(
+        &mut s,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"##,
+        w = width as u32 * cell,
+        h = height as u32 * cell
+    )
+    .unwrap();
+
+    let highlighted = last_move.map_or(vec![], |mv| board.render_move_cells(mv));
+
+    for y in 0..height {
+        for x in 0..width {
+            let fill = match board.render_cell(x, y) {
+                Cell::Blocked => "#555555",
+                _ if highlighted.contains(&(x, y)) => "#f6e27a",
+                _ => "#e8dcc0",
+            };
+            writeln!(
+                &mut s,
+                r##"<rect x="{}" y="{}" width="{c}" height="{c}" fill="{}" stroke="#333333" stroke-width="1"/>"##,
+                x as u32 * cell,
+                y as u32 * cell,
+                fill,
+                c = cell
+            )
+            .unwrap();
+        }
+    }
+
+    let heat = cell_weights(board, heatmap);
+    let max_heat = heat.values().copied().fold(0.0, f32::max);
+    if max_heat > 0.0 {
+        for (&(x, y), &weight) in &heat {
+            writeln!(
+                &mut s,
+                r##"<rect x="{}" y="{}" width="{c}" height="{c}" fill="#d03030" fill-opacity="{:.3}"/>"##,
+                x as u32 * cell,
+                y as u32 * cell,
+                0.8 * weight / max_heat,
+                c = cell
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(block) = board.render_block_size() {
+        let block = block as u32;
+        for i in (0..=width as u32).step_by(block as usize) {
+            let x = i * cell;
+            let h = height as u32 * cell;
+            writeln!(
+                &mut s,
+                r##"<line x1="{x}" y1="0" x2="{x}" y2="{h}" stroke="#000000" stroke-width="3"/>"##,
+                x = x,
+                h = h
+            )
+            .unwrap();
+        }
+        for i in (0..=height as u32).step_by(block as usize) {
+            let y = i * cell;
+            let w = width as u32 * cell;
+            writeln!(
+                &mut s,
+                r##"<line x1="0" y1="{y}" x2="{w}" y2="{y}" stroke="#000000" stroke-width="3"/>"##,
+                y = y,
+                w = w
+            )
+            .unwrap();
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let cx = x as u32 * cell + cell / 2;
+            let cy = y as u32 * cell + cell / 2;
+
+            match board.render_cell(x, y) {
+                Cell::Empty | Cell::Blocked => {}
+                Cell::Stone(player) => {
+                    writeln!(
+                        &mut s,
+                        r##"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="#000000" stroke-width="1"/>"##,
+                        cx,
+                        cy,
+                        cell * 2 / 5,
+                        player_color(player)
+                    )
+                    .unwrap();
+                }
+                Cell::Piece(symbol) => {
+                    writeln!(
+                        &mut s,
+                        r##"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                        cx,
+                        cy,
+                        cell * 4 / 5,
+                        escape(symbol)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    writeln!(&mut s, "</svg>").unwrap();
+    s
+}
+
+/// The number of moves listed below the grid drawn by [to_terminal].
+const TERMINAL_LISTED_MOVES: usize = 5;
+
+/// Draw `board` with `heatmap` as a grid for terminals that support 24-bit ANSI colors, followed by the moves with
+/// the largest weights. Stones are drawn as `x` and `o` for player A and B, blocked cells as `#`.
+pub fn to_terminal<B: RenderBoard>(board: &B, heatmap: &[(B::Move, f32)]) -> String {
+    let (width, height) = board.render_size();
+    let heat = cell_weights(board, heatmap);
+    let max_heat = heat.values().copied().fold(0.0, f32::max);
+
+    let mut s = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let symbol = match board.render_cell(x, y) {
+                Cell::Empty => '.',
+                Cell::Blocked => '#',
+                Cell::Stone(Player::A) => 'x',
+                Cell::Stone(Player::B) => 'o',
+                Cell::Piece(symbol) => symbol,
+            };
+
+            let weight = heat.get(&(x, y)).copied().unwrap_or(0.0);
+            let fraction = if max_heat > 0.0 { weight / max_heat } else { 0.0 };
+            let red = 40 + (fraction * 180.0) as u8;
+            write!(&mut s, "\x1b[48;2;{};40;40m {} ", red, symbol).unwrap();
+        }
+        writeln!(&mut s, "\x1b[0m").unwrap();
+    }
+
+    let mut sorted = heatmap.to_vec();
+    sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    for (mv, weight) in sorted.iter().take(TERMINAL_LISTED_MOVES) {
+        writeln!(&mut s, "{:?}: {:.3}", mv, weight).unwrap();
+    }
+
+    s
+}
+
+/// The fraction of the root visits of `tree` that went to each move, to be drawn as a heatmap.
+pub fn visit_heatmap<B: Board>(tree: &Tree<B>) -> Vec<(B::Move, f32)> {
+    let children = match tree[0].children {
+        Some(children) => children,
+        None => return vec![],
+    };
+
+    let total: i64 = children.iter().map(|c| tree[c].visits).sum();
+    children
+        .iter()
+        .map(|c| {
+            let fraction = if total > 0 {
+                tree[c].visits as f32 / total as f32
+            } else {
+                0.0
+            };
+            (tree[c].last_move.unwrap(), fraction)
+        })
+        .collect()
+}
+
+/// Sum the heatmap weights per cell, ignoring moves without cells and negative weights.
+fn cell_weights<B: RenderBoard>(board: &B, heatmap: &[(B::Move, f32)]) -> BTreeMap<(u8, u8), f32> {
+    let mut weights = BTreeMap::new();
+    for &(mv, weight) in heatmap {
+        if let Some(&cell) = board.render_move_cells(mv).last() {
+            *weights.entry(cell).or_insert(0.0) += weight.max(0.0);
+        }
+    }
+    weights
+}
+
+fn player_color(player: Player) -> &'static str {
+    match player {
+        Player::A => "#202020",
+        Player::B => "#f8f8f8",
+    }
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '&' => "&amp;".to_string(),
+        c => c.to_string(),
+    }
+}
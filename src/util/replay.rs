@@ -0,0 +1,303 @@
+//! A compact text format to store played games, so they can be re-examined later.
+//!
+//! Each game starts with a `start` line containing the FEN of the initial position,
+//! followed by one line per move with optional `key=value` annotations:
+//! ```text
+//! start x5o/7/7/7/7/7/o5x x 0 1
+//! f1 time=0.012 value=0.150
+//! b1 time=0.009
+//! ```
+//! Empty lines and lines starting with `#` are ignored. Positions and moves use the [ExternalBoard] notation.
+//!
+//! [ReplayRecorder] records all games played by [bot_game](crate::util::bot_game) in this format,
+//! it requires the `native` feature.
+use std::fmt::{Display, Formatter};
+
+use serde_json::{json, Value};
+
+use crate::ai::external::ExternalBoard;
+use crate::board::{Board, Outcome, Player};
+use crate::util::pgn::PgnGame;
+
+#[cfg(feature = "native")]
+pub use recorder::ReplayRecorder;
+
+/// A recorded game: the start position and the moves played from it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Replay<B: Board> {
+    pub start: B,
+    pub moves: Vec<ReplayMove<B>>,
+}
+
+/// A move together with its annotations.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReplayMove<B: Board> {
+    pub mv: B::Move,
+    /// Key-value pairs, for example the time the bot took or its evaluation.
+    /// Neither can contain whitespace, and keys can't contain `=`.
+    pub annotations: Vec<(String, String)>,
+}
+
+impl<B: Board> Replay<B> {
+    pub fn new(start: B) -> Self {
+        Replay { start, moves: vec![] }
+    }
+
+    /// The board after all moves have been played.
+    pub fn end(&self) -> B {
+        let mut board = self.start.clone();
+        for mv in &self.moves {
+            board.play(mv.mv);
+        }
+        board
+    }
+
+    /// All positions of the game, starting with `start` and ending with the position after the last move.
+    pub fn boards(&self) -> Vec<B> {
+        let mut board = self.start.clone();
+        let mut boards = vec![board.clone()];
+        for mv in &self.moves {
+            board.play(mv.mv);
+            boards.push(board.clone());
+        }
+        boards
+    }
+
+    /// Append `mv`, which must be available on the current end of the game.
+    pub fn push(&mut self, mv: B::Move, annotations: Vec<(String, String)>) {
+        let board = self.end();
+        assert!(
+            !board.is_done() && board.is_available_move(mv),
+            "Move {:?} is not available on {:?}",
+            mv,
+            board
+        );
+        for (key, value) in &annotations {
+            assert!(valid_key(key), "Invalid annotation key '{}'", key);
+            assert!(valid_value(value), "Invalid annotation value '{}'", value);
+        }
+        self.moves.push(ReplayMove { mv, annotations });
+    }
+
+    /// Convert to a [PgnGame] using `notation` for the moves, the annotations of each move become its comment.
+    pub fn to_pgn(&self, tags: Vec<(String, String)>, notation: impl FnMut(&B, B::Move) -> String) -> PgnGame {
+        let moves: Vec<B::Move> = self.moves.iter().map(|mv| mv.mv).collect();
+        let mut game = PgnGame::from_game(&self.start, &moves, tags, notation);
+
+        for (comment, mv) in game.comments.iter_mut().zip(&self.moves) {
+            if !mv.annotations.is_empty() {
+                let pairs: Vec<String> = mv.annotations.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                *comment = Some(pairs.join(" "));
+            }
+        }
+
+        game
+    }
+}
+
+impl<B: ExternalBoard> Replay<B> {
+    /// Convert to a JSON object
+    /// `{"start": fen, "outcome": null | "A" | "B" | "draw", "moves": [{"move": ..., "<key>": "<value>", ...}, ...]}`.
+    pub fn to_json(&self) -> Value {
+        let mut board = self.start.clone();
+        let moves: Vec<Value> = self
+            .moves
+            .iter()
+            .map(|mv| {
+                let mut object = serde_json::Map::new();
+                object.insert("move".to_string(), json!(board.external_format_move(mv.mv)));
+                for (key, value) in &mv.annotations {
+                    object.insert(key.clone(), json!(value));
+                }
+                board.play(mv.mv);
+                Value::Object(object)
+            })
+            .collect();
+
+        let outcome = match board.outcome() {
+            None => Value::Null,
+            Some(Outcome::WonBy(Player::A)) => json!("A"),
+            Some(Outcome::WonBy(Player::B)) => json!("B"),
+            Some(Outcome::Draw) => json!("draw"),
+        };
+
+        json!({
+            "start": self.start.external_fen(),
+            "outcome": outcome,
+            "moves": moves,
+        })
+    }
+}
+
+impl<B: Board> ReplayMove<B> {
+    /// Get the value of the annotation `key`, if it exists.
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.annotations.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+impl<B: ExternalBoard> Display for Replay<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "start {}", self.start.external_fen())?;
+
+        let mut board = self.start.clone();
+        for mv in &self.moves {
+            write!(f, "{}", board.external_format_move(mv.mv))?;
+            for (key, value) in &mv.annotations {
+                write!(f, " {}={}", key, value)?;
+            }
+            writeln!(f)?;
+            board.play(mv.mv);
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse all games in `input`, checking that every move is available when it is played.
+pub fn parse_replays<B: ExternalBoard>(input: &str) -> Result<Vec<Replay<B>>, String> {
+    let mut replays: Vec<Replay<B>> = vec![];
+    let mut board = None;
+
+    for (line_i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |msg: &str| format!("Line {}: {} in '{}'", line_i + 1, msg, line);
+
+        if let Some(fen) = line.strip_prefix("start ") {
+            let start = B::external_from_fen(fen.trim()).ok_or_else(|| error("Invalid fen"))?;
+            board = Some(start.clone());
+            replays.push(Replay::new(start));
+            continue;
+        }
+
+        let (board, replay) = match (board.as_mut(), replays.last_mut()) {
+            (Some(board), Some(replay)) => (board, replay),
+            _ => return Err(error("Move before the first start line")),
+        };
+
+        let mut tokens = line.split_whitespace();
+        let mv_str = tokens.next().unwrap();
+        if board.is_done() {
+            return Err(error("Move played after the game was done"));
+        }
+        let mv = board
+            .external_parse_move(mv_str)
+            .filter(|&mv| board.is_available_move(mv))
+            .ok_or_else(|| error("Invalid move"))?;
+
+        let annotations = tokens
+            .map(|token| match token.split_once('=') {
+                Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+                _ => Err(error("Invalid annotation")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        board.play(mv);
+        replay.moves.push(ReplayMove { mv, annotations });
+    }
+
+    Ok(replays)
+}
+
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(|c: char| c.is_whitespace() || c == '=')
+}
+
+fn valid_value(value: &str) -> bool {
+    !value.contains(char::is_whitespace)
+}
+
+#[cfg(feature = "native")]
+mod recorder {
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::sync::Mutex;
+
+    use crate::ai::external::ExternalBoard;
+    use crate::util::bot_game::GameEvent;
+    use crate::util::replay::{Replay, ReplayMove};
+
+    /// Records the games of a [bot_game](crate::util::bot_game) match, writing each game to `output` once it ends.
+    ///
+    /// Pass `|event| recorder.observe(event)` as the observer to [run_observed](crate::util::bot_game::run_observed),
+    /// or use [run_recorded](crate::util::bot_game::run_recorded) which does this.
+    /// Every move is annotated with the side that played it (`bot=l` or `bot=r`), the time it took in seconds
+    /// and the value reported by the bot if any.
+    #[derive(Debug)]
+    pub struct ReplayRecorder<B: ExternalBoard, W: Write> {
+        state: Mutex<State<B, W>>,
+    }
+
+    #[derive(Debug)]
+    struct State<B: ExternalBoard, W: Write> {
+        output: W,
+        games: HashMap<u32, Replay<B>>,
+        error: Option<std::io::Error>,
+    }
+
+    impl<B: ExternalBoard, W: Write> ReplayRecorder<B, W> {
+        pub fn new(output: W) -> Self {
+            let state = State {
+                output,
+                games: HashMap::new(),
+                error: None,
+            };
+            ReplayRecorder {
+                state: Mutex::new(state),
+            }
+        }
+
+        /// Handle an event of the match.
+        pub fn observe(&self, event: &GameEvent<B>) {
+            let mut state = self.state.lock().unwrap();
+            let state = &mut *state;
+
+            match *event {
+                GameEvent::Start { game_id, board } => {
+                    state.games.insert(game_id, Replay::new(board.clone()));
+                }
+                GameEvent::Move {
+                    game_id,
+                    mv,
+                    left,
+                    time,
+                    value,
+                    ..
+                } => {
+                    let mut annotations = vec![
+                        ("bot".to_string(), if left { "l" } else { "r" }.to_string()),
+                        ("time".to_string(), format!("{:.3}", time)),
+                    ];
+                    if let Some(value) = value {
+                        annotations.push(("value".to_string(), format!("{:.3}", value)));
+                    }
+                    // the moves come from the game itself, so they don't need to be checked again
+                    if let Some(replay) = state.games.get_mut(&game_id) {
+                        replay.moves.push(ReplayMove { mv, annotations });
+                    }
+                }
+                GameEvent::End { game_id, .. } => {
+                    if let Some(replay) = state.games.remove(&game_id) {
+                        // keep the first error, don't abort the match because of it
+                        if state.error.is_none() {
+                            let result = write!(state.output, "{}", replay).and_then(|_| state.output.flush());
+                            state.error = result.err();
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Returns the output, or the first error that happened while writing to it.
+        pub fn finish(self) -> std::io::Result<W> {
+            let state = self.state.into_inner().unwrap();
+            match state.error {
+                Some(e) => Err(e),
+                None => Ok(state.output),
+            }
+        }
+    }
+}
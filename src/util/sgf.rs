@@ -0,0 +1,271 @@
+//! Reading and writing tree-structured game records in the [SGF](https://www.red-bean.com/sgf/) format.
+//!
+//! A game record is a tree of nodes, each node has a list of properties. The first child of a node is the main line,
+//! the other children are variations. Moves are stored in the `B` and `W` properties, where `B` is the first player
+//! ([Player::A]) and `W` the second player ([Player::B]), as in Go and Hex.
+//! The notation of the moves themselves is supplied by the caller.
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::board::{Board, Player};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SgfNode {
+    pub properties: Vec<(String, Vec<String>)>,
+    pub children: Vec<SgfNode>,
+}
+
+impl SgfNode {
+    pub fn new(properties: Vec<(String, Vec<String>)>) -> Self {
+        SgfNode {
+            properties,
+            children: vec![],
+        }
+    }
+
+    /// Build a linear record of the game played from `start` with `moves`,
+    /// using `notation` to convert each move to a property value.
+    /// `root_properties` are stored in the root node, which does not contain a move.
+    pub fn from_game<B: Board>(
+        root_properties: Vec<(String, Vec<String>)>,
+        start: &B,
+        moves: &[B::Move],
+        notation: impl FnMut(&B, B::Move) -> String,
+    ) -> Self {
+        let mut root = SgfNode::new(root_properties);
+        root.push_line(start, moves, notation);
+        root
+    }
+
+    /// Append the moves as a new line starting at this node. If this node already has children
+    /// the line becomes a variation.
+    pub fn push_line<B: Board>(
+        &mut self,
+        start: &B,
+        moves: &[B::Move],
+        mut notation: impl FnMut(&B, B::Move) -> String,
+    ) {
+        let mut board = start.clone();
+        let mut node = self;
+
+        for &mv in moves {
+            let value = notation(&board, mv);
+            let ident = player_ident(board.next_player());
+            board.play(mv);
+
+            node.children.push(SgfNode::new(vec![(ident.to_string(), vec![value])]));
+            node = node.children.last_mut().unwrap();
+        }
+    }
+
+    /// Get the first value of the property `ident`, if it exists.
+    pub fn property(&self, ident: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == ident)
+            .and_then(|(_, v)| v.first())
+            .map(|v| v.as_str())
+    }
+
+    /// Set the property `ident` to the single value `value`, replacing the previous values if any.
+    pub fn set_property(&mut self, ident: &str, value: &str) {
+        match self.properties.iter_mut().find(|(k, _)| k == ident) {
+            Some((_, v)) => *v = vec![value.to_string()],
+            None => self.properties.push((ident.to_string(), vec![value.to_string()])),
+        }
+    }
+
+    /// The move stored in this node as `(player, value)`, if any.
+    pub fn move_value(&self) -> Option<(Player, &str)> {
+        self.property("B")
+            .map(|v| (Player::A, v))
+            .or_else(|| self.property("W").map(|v| (Player::B, v)))
+    }
+
+    /// Iterate over the main line, starting with this node.
+    pub fn main_line(&self) -> impl Iterator<Item = &SgfNode> {
+        std::iter::successors(Some(self), |node| node.children.first())
+    }
+
+    /// Convert the moves in the main line back to actual moves, starting from `start`.
+    /// `parse` should return `None` if the string is not a valid move on the given board.
+    pub fn replay_main_line<B: Board>(
+        &self,
+        start: &B,
+        mut parse: impl FnMut(&B, &str) -> Option<B::Move>,
+    ) -> Result<Vec<B::Move>, String> {
+        let mut board = start.clone();
+        let mut moves = vec![];
+
+        for (player, value) in self.main_line().filter_map(|node| node.move_value()) {
+            if board.is_done() {
+                return Err(format!("Move '{}' played after the game was done", value));
+            }
+            if player != board.next_player() {
+                return Err(format!("Move '{}' played by {:?} out of turn", value, player));
+            }
+
+            let mv = parse(&board, value)
+                .filter(|&mv| board.is_available_move(mv))
+                .ok_or_else(|| format!("Move '{}' is not valid on board {:?}", value, board))?;
+
+            board.play(mv);
+            moves.push(mv);
+        }
+
+        Ok(moves)
+    }
+
+    fn fmt_sequence(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, ";")?;
+        for (ident, values) in &self.properties {
+            write!(f, "{}", ident)?;
+            for value in values {
+                write!(f, "[{}]", value.replace('\\', "\\\\").replace(']', "\\]"))?;
+            }
+        }
+
+        match self.children.len() {
+            0 => Ok(()),
+            1 => self.children[0].fmt_sequence(f),
+            _ => {
+                for child in &self.children {
+                    writeln!(f)?;
+                    write!(f, "(")?;
+                    child.fmt_sequence(f)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for SgfNode {
+    /// Write this node as a complete game tree.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(")?;
+        self.fmt_sequence(f)?;
+        write!(f, ")")
+    }
+}
+
+/// Parse all game trees in `input`, returning the root node of each.
+pub fn parse_sgf(input: &str) -> Result<Vec<SgfNode>, String> {
+    let mut chars = input.chars().peekable();
+    let mut trees = vec![];
+
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => return Ok(trees),
+            Some('(') => trees.push(parse_tree(&mut chars)?),
+            Some(c) => return Err(format!("Expected '(', got '{}'", c)),
+        }
+    }
+}
+
+/// Parse a game tree, the opening '(' has already been consumed.
+fn parse_tree(chars: &mut Peekable<Chars>) -> Result<SgfNode, String> {
+    // parse the sequence of nodes
+    let mut sequence = vec![];
+    loop {
+        skip_whitespace(chars);
+        if chars.peek() != Some(&';') {
+            break;
+        }
+        chars.next();
+        sequence.push(SgfNode::new(parse_properties(chars)?));
+    }
+
+    // parse the variations
+    let mut variations = vec![];
+    loop {
+        skip_whitespace(chars);
+        match chars.next() {
+            Some('(') => variations.push(parse_tree(chars)?),
+            Some(')') => break,
+            Some(c) => return Err(format!("Unexpected character '{}'", c)),
+            None => return Err("Missing closing ')'".to_string()),
+        }
+    }
+
+    // link the sequence together, from the back
+    let mut node = sequence.pop().ok_or("Game tree without nodes")?;
+    node.children = variations;
+    while let Some(mut parent) = sequence.pop() {
+        parent.children = vec![node];
+        node = parent;
+    }
+    Ok(node)
+}
+
+fn parse_properties(chars: &mut Peekable<Chars>) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut properties = vec![];
+
+    loop {
+        skip_whitespace(chars);
+
+        let mut ident = String::new();
+        while let Some(&c) = chars.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            // lowercase letters were allowed in old versions of the format and should be ignored
+            if c.is_ascii_uppercase() {
+                ident.push(c);
+            }
+            chars.next();
+        }
+        if ident.is_empty() {
+            return Ok(properties);
+        }
+
+        let mut values = vec![];
+        loop {
+            skip_whitespace(chars);
+            if chars.peek() != Some(&'[') {
+                break;
+            }
+            chars.next();
+            values.push(parse_value(chars)?);
+        }
+        if values.is_empty() {
+            return Err(format!("Property '{}' without values", ident));
+        }
+
+        properties.push((ident, values));
+    }
+}
+
+/// Parse a property value, the opening '[' has already been consumed.
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            ']' => return Ok(value),
+            '\\' => match chars.next() {
+                // soft line break
+                Some('\n') => {}
+                Some(c) => value.push(c),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+    Err("Missing closing ']'".to_string())
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while matches!(chars.peek(), Some(c) if c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn player_ident(player: Player) -> &'static str {
+    match player {
+        Player::A => "B",
+        Player::B => "W",
+    }
+}
@@ -0,0 +1,223 @@
+//! A terminal view that follows an ongoing [bot_game](crate::util::bot_game) match live.
+//!
+//! Pass `|event| spectator.observe(event)` as the observer to [run_observed](crate::util::bot_game::run_observed).
+//! The screen is redrawn with ANSI escape codes after every event of the followed game.
+use std::fmt::Write as _;
+use std::io::Write;
+use std::sync::Mutex;
+
+use crate::ai::external::ExternalBoard;
+use crate::board::{Outcome, Player};
+use crate::util::bot_game::GameEvent;
+
+/// How many of the last moves are shown.
+const SHOWN_MOVES: usize = 40;
+
+#[derive(Debug)]
+pub struct TerminalSpectator<B: ExternalBoard, W: Write> {
+    state: Mutex<State<B, W>>,
+}
+
+#[derive(Debug)]
+struct State<B: ExternalBoard, W: Write> {
+    output: W,
+    /// Only follow this game, if `None` the first game that starts is followed, then the next one and so on.
+    fixed_game: Option<u32>,
+
+    following: Option<u32>,
+    board: Option<B>,
+    left_player: Option<Player>,
+    moves: Vec<String>,
+    value_l: Option<f32>,
+    value_r: Option<f32>,
+    time_l: f32,
+    time_r: f32,
+
+    /// The left player of each game that has started, used to count the results.
+    left_players: Vec<(u32, Player)>,
+    wins_l: u32,
+    draws: u32,
+    wins_r: u32,
+}
+
+impl<B: ExternalBoard, W: Write> TerminalSpectator<B, W> {
+    /// Create a spectator writing to `output`, usually `std::io::stdout()`.
+    /// If `game_id` is `None` the games are followed one after the other as they start.
+    pub fn new(game_id: Option<u32>, output: W) -> Self {
+        let state = State {
+            output,
+            fixed_game: game_id,
+            following: None,
+            board: None,
+            left_player: None,
+            moves: vec![],
+            value_l: None,
+            value_r: None,
+            time_l: 0.0,
+            time_r: 0.0,
+            left_players: vec![],
+            wins_l: 0,
+            draws: 0,
+            wins_r: 0,
+        };
+        TerminalSpectator {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Handle an event of the match, redrawing the screen if it belongs to the followed game.
+    pub fn observe(&self, event: &GameEvent<B>) {
+        let mut state = self.state.lock().unwrap();
+        state.handle(event);
+    }
+
+    pub fn into_output(self) -> W {
+        self.state.into_inner().unwrap().output
+    }
+}
+
+impl<B: ExternalBoard, W: Write> State<B, W> {
+    fn handle(&mut self, event: &GameEvent<B>) {
+        match *event {
+            GameEvent::Start { game_id, board } => {
+                let follow = match self.fixed_game {
+                    Some(fixed) => fixed == game_id,
+                    None => self.following.is_none(),
+                };
+                if follow {
+                    self.following = Some(game_id);
+                    self.board = Some(board.clone());
+                    self.left_player = None;
+                    self.moves.clear();
+                    self.value_l = None;
+                    self.value_r = None;
+                    self.time_l = 0.0;
+                    self.time_r = 0.0;
+                    self.redraw(None);
+                }
+            }
+            GameEvent::Move {
+                game_id,
+                board,
+                mv,
+                left,
+                time,
+                value,
+            } => {
+                if !self.left_players.iter().any(|&(id, _)| id == game_id) {
+                    let player = board.next_player();
+                    let left_player = if left { player } else { player.other() };
+                    self.left_players.push((game_id, left_player));
+                }
+
+                if self.following == Some(game_id) {
+                    self.moves.push(board.external_format_move(mv));
+                    self.board = Some(board.clone_and_play(mv));
+                    if left {
+                        self.value_l = value;
+                        self.time_l += time;
+                        self.left_player = Some(board.next_player());
+                    } else {
+                        self.value_r = value;
+                        self.time_r += time;
+                        self.left_player = Some(board.next_player().other());
+                    }
+                    self.redraw(None);
+                }
+            }
+            GameEvent::End { game_id, outcome, .. } => {
+                let index = self.left_players.iter().position(|&(id, _)| id == game_id);
+                let left_player = index.map(|index| self.left_players.swap_remove(index).1);
+
+                match (outcome, left_player) {
+                    (Outcome::Draw, _) => self.draws += 1,
+                    (Outcome::WonBy(winner), Some(left_player)) => {
+                        if winner == left_player {
+                            self.wins_l += 1
+                        } else {
+                            self.wins_r += 1
+                        }
+                    }
+                    // the game ended without any moves being played
+                    (Outcome::WonBy(_), None) => {}
+                }
+
+                if self.following == Some(game_id) {
+                    self.redraw(Some(outcome));
+                    if self.fixed_game.is_none() {
+                        self.following = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn redraw(&mut self, outcome: Option<Outcome>) {
+        let board = match &self.board {
+            Some(board) => board,
+            None => return,
+        };
+        let mut s = String::new();
+
+        // clear the screen and move the cursor to the top left
+        s.push_str("\x1b[2J\x1b[H");
+
+        let game_id = self.following.unwrap();
+        match outcome {
+            None => writeln!(&mut s, "Game {}, {:?} to play", game_id, board.next_player()).unwrap(),
+            Some(outcome) => writeln!(&mut s, "Game {} finished: {:?}", game_id, outcome).unwrap(),
+        }
+        writeln!(&mut s).unwrap();
+        writeln!(&mut s, "{}", board).unwrap();
+
+        let player_str = |player: Option<Player>| match player {
+            Some(player) => format!("{:?}", player),
+            None => "?".to_string(),
+        };
+        let value_str = |value: Option<f32>| match value {
+            Some(value) => format!("{:+.3}", value),
+            None => "-".to_string(),
+        };
+        writeln!(
+            &mut s,
+            "left  ({}): value {:>6}, time {:.2}s",
+            player_str(self.left_player),
+            value_str(self.value_l),
+            self.time_l
+        )
+        .unwrap();
+        writeln!(
+            &mut s,
+            "right ({}): value {:>6}, time {:.2}s",
+            player_str(self.left_player.map(Player::other)),
+            value_str(self.value_r),
+            self.time_r
+        )
+        .unwrap();
+        writeln!(&mut s).unwrap();
+
+        let skipped = self.moves.len().saturating_sub(SHOWN_MOVES);
+        if skipped > 0 {
+            write!(&mut s, "... ").unwrap();
+        }
+        for (i, mv) in self.moves.iter().enumerate().skip(skipped) {
+            write!(&mut s, "{}.{} ", i + 1, mv).unwrap();
+        }
+        writeln!(&mut s).unwrap();
+        writeln!(&mut s).unwrap();
+
+        writeln!(
+            &mut s,
+            "Finished games: {}, left {} / draw {} / right {}",
+            self.wins_l + self.draws + self.wins_r,
+            self.wins_l,
+            self.draws,
+            self.wins_r
+        )
+        .unwrap();
+
+        // the spectator is a debugging aid, don't abort the match if the terminal goes away
+        let _ = self.output.write_all(s.as_bytes());
+        let _ = self.output.flush();
+    }
+}
@@ -0,0 +1,342 @@
+//! Round-robin tournaments between more than two bots, and gauntlets of one candidate against reference opponents.
+//!
+//! Every pairing is played as a [bot_game](crate::util::bot_game) match, after which Elo ratings are fitted to all
+//! results at once with [fit_elo], or with [fit_elo_anchored] for a gauntlet.
+use std::fmt::{Display, Formatter};
+
+use serde_json::{json, Value};
+
+use crate::ai::Bot;
+use crate::board::Board;
+use crate::util::bot_game;
+use crate::util::bot_game::{write_status, BotGameResult, GameEvent, MatchOptions};
+use crate::util::elo::{fit_elo, fit_elo_anchored};
+
+/// Play every pairing of `bots` against each other, starting from the boards given by `start`.
+///
+/// Each pairing is a match of `games_per_pair` games, or twice that if `both_sides` is true,
+/// see [run](bot_game::run). All bots are the same type, bots of different types can be put in an enum or a box.
+#[must_use]
+pub fn round_robin<B: Board, T: Bot<B>, F: Fn() -> T + Sync>(
+    bots: &[F],
+    start: impl Fn() -> B + Sync,
+    games_per_pair: u32,
+    both_sides: bool,
+) -> TournamentResult {
+    round_robin_with(bots, start, games_per_pair, both_sides, &MatchOptions::default())
+}
+
+/// Same as [round_robin], but every pairing is played with `options`, see [run_with](bot_game::run_with).
+///
+/// If [status_file](MatchOptions::status_file) is set, the tournament results so far are written to it after each
+/// pairing instead of the results of the individual matches.
+#[must_use]
+pub fn round_robin_with<B: Board, T: Bot<B>, F: Fn() -> T + Sync>(
+    bots: &[F],
+    start: impl Fn() -> B + Sync,
+    games_per_pair: u32,
+    both_sides: bool,
+    options: &MatchOptions<B>,
+) -> TournamentResult {
+    let n = bots.len();
+    assert!(n >= 2, "A tournament needs at least two bots");
+
+    let mut names = vec![String::new(); n];
+    let mut wins = vec![vec![0; n]; n];
+    let mut draws = vec![vec![0; n]; n];
+
+    let status_file = options.status_file.as_ref();
+    let match_options = MatchOptions {
+        status_file: None,
+        ..options.clone()
+    };
+    let mut pairings_done = 0;
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let result = bot_game::run_with(
+                &start,
+                &bots[i],
+                &bots[j],
+                games_per_pair,
+                both_sides,
+                &match_options,
+                |_: &GameEvent<B>| {},
+            );
+
+            names[i] = result.debug_l;
+            names[j] = result.debug_r;
+            wins[i][j] = result.wins_l;
+            wins[j][i] = result.wins_r;
+            draws[i][j] = result.draws;
+            draws[j][i] = result.draws;
+            pairings_done += 1;
+
+            if let Some(path) = status_file {
+                let partial = TournamentResult::new(names.clone(), wins.clone(), draws.clone());
+                let mut status = partial.to_json();
+                status["pairings"] = json!(pairings_done);
+                status["max_pairings"] = json!(n * (n - 1) / 2);
+                // the status is only informative, a failure should not abort the tournament
+                if let Err(e) = write_status(path, &status) {
+                    eprintln!("Failed to write status to {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    TournamentResult::new(names, wins, draws)
+}
+
+/// Structure returned by the function [`round_robin`], players are indexed in the order they were passed.
+#[derive(Debug)]
+pub struct TournamentResult {
+    // bot debug strings
+    pub names: Vec<String>,
+
+    //wins[i][j] is the number of games bot i won against bot j
+    pub wins: Vec<Vec<u32>>,
+    //draws[i][j] is the number of draws between bot i and bot j
+    pub draws: Vec<Vec<u32>>,
+
+    //fitted elo ratings, averaging to zero
+    pub elo: Vec<f32>,
+}
+
+impl TournamentResult {
+    fn new(names: Vec<String>, wins: Vec<Vec<u32>>, draws: Vec<Vec<u32>>) -> Self {
+        let n = names.len();
+        let points = (0..n)
+            .map(|i| (0..n).map(|j| wins[i][j] as f32 + 0.5 * draws[i][j] as f32).collect())
+            .collect::<Vec<Vec<f32>>>();
+        let games = (0..n)
+            .map(|i| (0..n).map(|j| wins[i][j] + wins[j][i] + draws[i][j]).collect())
+            .collect::<Vec<Vec<u32>>>();
+        let elo = fit_elo(&points, &games);
+
+        TournamentResult {
+            names,
+            wins,
+            draws,
+            elo,
+        }
+    }
+
+    /// The number of games played between bot `i` and bot `j`.
+    pub fn games(&self, i: usize, j: usize) -> u32 {
+        self.wins[i][j] + self.wins[j][i] + self.draws[i][j]
+    }
+
+    /// The score of bot `i` against bot `j` in `0..=1`, `None` if they didn't play.
+    pub fn score(&self, i: usize, j: usize) -> Option<f32> {
+        let games = self.games(i, j);
+        if games == 0 {
+            None
+        } else {
+            Some((self.wins[i][j] as f32 + 0.5 * self.draws[i][j] as f32) / games as f32)
+        }
+    }
+
+    /// The results as a JSON object with the fields of this struct.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "names": self.names,
+            "wins": self.wins,
+            "draws": self.draws,
+            "elo": self.elo,
+        })
+    }
+}
+
+/// Formats the crosstable, with the scores in percent and the fitted Elo ratings in the last column.
+impl Display for TournamentResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let n = self.names.len();
+        let labels = (0..n)
+            .map(|i| format!("{} {}", i + 1, self.names[i]))
+            .collect::<Vec<_>>();
+        let width = labels.iter().map(|s| s.len()).max().unwrap_or(0);
+
+        write!(f, "{:width$}", "", width = width)?;
+        for i in 0..n {
+            write!(f, "  {:>6}", i + 1)?;
+        }
+        writeln!(f, "  {:>6}", "elo")?;
+
+        for (i, label) in labels.iter().enumerate() {
+            write!(f, "{:width$}", label, width = width)?;
+            for j in 0..n {
+                match self.score(i, j) {
+                    Some(score) => write!(f, "  {:>6.1}", score * 100.0)?,
+                    None => write!(f, "  {:>6}", "-")?,
+                }
+            }
+            writeln!(f, "  {:>+6.0}", self.elo[i])?;
+        }
+        Ok(())
+    }
+}
+
+/// Play `candidate` against each of the `opponents`, whose ratings are given by `anchors`,
+/// starting from the boards given by `start`.
+///
+/// Each pairing is a match of `games_per_opponent` games, or twice that if `both_sides` is true,
+/// see [run](bot_game::run). The candidate is rated against the fixed ratings of the opponents,
+/// so the ratings of different candidates against the same anchors can be compared.
+/// If the opponents have no known ratings, all anchors can be zero.
+#[must_use]
+pub fn gauntlet<B: Board, C: Bot<B>, T: Bot<B>, F: Fn() -> T + Sync>(
+    candidate: impl Fn() -> C + Sync,
+    opponents: &[F],
+    anchors: &[f32],
+    start: impl Fn() -> B + Sync,
+    games_per_opponent: u32,
+    both_sides: bool,
+) -> GauntletResult {
+    gauntlet_with(
+        candidate,
+        opponents,
+        anchors,
+        start,
+        games_per_opponent,
+        both_sides,
+        &MatchOptions::default(),
+    )
+}
+
+/// Same as [gauntlet], but every pairing is played with `options`, see [run_with](bot_game::run_with).
+///
+/// If [status_file](MatchOptions::status_file) is set, the gauntlet results so far are written to it after each
+/// opponent instead of the results of the individual matches.
+#[must_use]
+pub fn gauntlet_with<B: Board, C: Bot<B>, T: Bot<B>, F: Fn() -> T + Sync>(
+    candidate: impl Fn() -> C + Sync,
+    opponents: &[F],
+    anchors: &[f32],
+    start: impl Fn() -> B + Sync,
+    games_per_opponent: u32,
+    both_sides: bool,
+    options: &MatchOptions<B>,
+) -> GauntletResult {
+    assert!(!opponents.is_empty(), "A gauntlet needs at least one opponent");
+    assert_eq!(opponents.len(), anchors.len(), "Every opponent needs an anchor rating");
+
+    let status_file = options.status_file.as_ref();
+    let match_options = MatchOptions {
+        status_file: None,
+        ..options.clone()
+    };
+    let mut results = vec![];
+
+    for opponent in opponents {
+        let result = bot_game::run_with(
+            &start,
+            &candidate,
+            opponent,
+            games_per_opponent,
+            both_sides,
+            &match_options,
+            |_: &GameEvent<B>| {},
+        );
+        results.push(result);
+
+        if let Some(path) = status_file {
+            let partial = GauntletResult::new(anchors[..results.len()].to_vec(), results.clone());
+            let mut status = partial.to_json();
+            status["max_opponents"] = json!(opponents.len());
+            // the status is only informative, a failure should not abort the gauntlet
+            if let Err(e) = write_status(path, &status) {
+                eprintln!("Failed to write status to {:?}: {}", path, e);
+            }
+        }
+    }
+
+    GauntletResult::new(anchors.to_vec(), results)
+}
+
+/// Structure returned by the function [`gauntlet`], opponents are indexed in the order they were passed.
+#[derive(Debug)]
+pub struct GauntletResult {
+    // candidate bot debug string
+    pub candidate: String,
+    // opponent bot debug strings
+    pub opponents: Vec<String>,
+    // the fixed ratings of the opponents
+    pub anchors: Vec<f32>,
+
+    //results[i] is the match of the candidate (left) against opponent i (right)
+    pub results: Vec<BotGameResult>,
+
+    //the rating of the candidate fitted to all results, on the same scale as the anchors
+    pub elo: f32,
+}
+
+impl GauntletResult {
+    fn new(anchors: Vec<f32>, results: Vec<BotGameResult>) -> Self {
+        let points = results
+            .iter()
+            .map(|r| r.wins_l as f32 + 0.5 * r.draws as f32)
+            .collect::<Vec<f32>>();
+        let games = results.iter().map(|r| r.game_count).collect::<Vec<u32>>();
+        let elo = fit_elo_anchored(&anchors, &points, &games);
+
+        GauntletResult {
+            candidate: results[0].debug_l.clone(),
+            opponents: results.iter().map(|r| r.debug_r.clone()).collect(),
+            anchors,
+            results,
+            elo,
+        }
+    }
+
+    /// The results as a JSON object with the fields of this struct.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "candidate": self.candidate,
+            "opponents": self.opponents,
+            "anchors": self.anchors,
+            "results": self.results.iter().map(BotGameResult::to_json).collect::<Vec<_>>(),
+            "elo": self.elo,
+        })
+    }
+}
+
+/// Formats a table with the results against every opponent, followed by the combined rating of the candidate.
+impl Display for GauntletResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let labels = (0..self.opponents.len())
+            .map(|i| format!("{} {}", i + 1, self.opponents[i]))
+            .collect::<Vec<_>>();
+        let width = labels.iter().map(|s| s.len()).max().unwrap_or(0);
+
+        writeln!(f, "{}", self.candidate)?;
+        writeln!(
+            f,
+            "{:width$}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}",
+            "",
+            "anchor",
+            "games",
+            "wins",
+            "draws",
+            "losses",
+            "elo",
+            width = width
+        )?;
+        for (i, label) in labels.iter().enumerate() {
+            let result = &self.results[i];
+            writeln!(
+                f,
+                "{:width$}  {:>+6.0}  {:>6}  {:>6}  {:>6}  {:>6}  {:>+6.0}",
+                label,
+                self.anchors[i],
+                result.game_count,
+                result.wins_l,
+                result.draws,
+                result.wins_r,
+                self.anchors[i] + result.elo_l,
+                width = width
+            )?;
+        }
+        writeln!(f, "combined elo: {:+.0}", self.elo)
+    }
+}
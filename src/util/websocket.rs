@@ -0,0 +1,142 @@
+//! Broadcast live games to WebSocket clients as JSON, so matches can be watched in a browser in real time.
+//!
+//! Requires the `websocket` feature. A typical use is passing
+//! `|event| broadcaster.send_event(event)` as the observer to [run_observed](crate::util::bot_game::run_observed).
+//!
+//! Each event is sent as a single text message, one of:
+//! * `{"type": "start", "game": id, "fen": ...}`
+//! * `{"type": "move", "game": id, "fen": ..., "move": ..., "bot": "l" | "r", "time": ..., "value": null | ...}`
+//! * `{"type": "end", "game": id, "fen": ..., "outcome": "A" | "B" | "draw"}`
+//!
+//! For move events `fen` is the board before the move was played.
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::json;
+use tungstenite::{Message, WebSocket};
+
+use crate::ai::external::ExternalBoard;
+use crate::board::{Outcome, Player};
+use crate::util::bot_game::GameEvent;
+
+/// Accepts WebSocket clients on a background thread and sends messages to all of them from another one.
+#[derive(Debug)]
+pub struct LiveBroadcaster {
+    local_addr: SocketAddr,
+    clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+    sender: Mutex<Sender<String>>,
+}
+
+impl LiveBroadcaster {
+    /// Start listening for clients on `addr`.
+    pub fn bind(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+
+        let clients = Arc::new(Mutex::new(vec![]));
+        let thread_clients = Arc::clone(&clients);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                // don't let a single stuck client block the games, or new clients during the handshake
+                let timeout = Some(Duration::from_secs(1));
+                if stream.set_write_timeout(timeout).is_err() || stream.set_read_timeout(timeout).is_err() {
+                    continue;
+                }
+
+                if let Ok(socket) = tungstenite::accept(stream) {
+                    thread_clients.lock().unwrap().push(socket);
+                }
+            }
+        });
+
+        let (sender, receiver) = channel::<String>();
+        let writer_clients = Arc::clone(&clients);
+
+        // stops once the broadcaster is dropped
+        std::thread::spawn(move || {
+            for message in receiver {
+                let mut clients = writer_clients.lock().unwrap();
+                let mut i = 0;
+                while i < clients.len() {
+                    if clients[i].write_message(Message::Text(message.clone())).is_ok() {
+                        i += 1;
+                    } else {
+                        clients.swap_remove(i);
+                    }
+                }
+            }
+        });
+
+        Ok(LiveBroadcaster {
+            local_addr,
+            clients,
+            sender: Mutex::new(sender),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Send `message` to all connected clients, dropping the clients that can no longer be reached.
+    ///
+    /// The message is written by a background thread, so this never blocks on slow clients.
+    pub fn broadcast(&self, message: &str) {
+        // the writer thread only stops when `self` is dropped
+        let _ = self.sender.lock().unwrap().send(message.to_string());
+    }
+
+    /// Send `event` to all connected clients as JSON.
+    pub fn send_event<B: ExternalBoard>(&self, event: &GameEvent<B>) {
+        let message = match *event {
+            GameEvent::Start { game_id, board } => json!({
+                "type": "start",
+                "game": game_id,
+                "fen": board.external_fen(),
+            }),
+            GameEvent::Move {
+                game_id,
+                board,
+                mv,
+                left,
+                time,
+                value,
+            } => json!({
+                "type": "move",
+                "game": game_id,
+                "fen": board.external_fen(),
+                "move": board.external_format_move(mv),
+                "bot": if left { "l" } else { "r" },
+                "time": time,
+                "value": value,
+            }),
+            GameEvent::End {
+                game_id,
+                board,
+                outcome,
+            } => json!({
+                "type": "end",
+                "game": game_id,
+                "fen": board.external_fen(),
+                "outcome": match outcome {
+                    Outcome::WonBy(Player::A) => "A",
+                    Outcome::WonBy(Player::B) => "B",
+                    Outcome::Draw => "draw",
+                },
+            }),
+        };
+
+        self.broadcast(&message.to_string());
+    }
+}
@@ -0,0 +1,22 @@
+//! Zobrist hashing, used by games to keep a hash of the position up to date while moves are played.
+//!
+//! A Zobrist hash is the xor of a random key for every feature of a position, for example a piece on a tile.
+//! Playing a move only changes a few features, so the hash can be updated with a few xors instead of hashing
+//! the whole board again. This makes hashes essentially free for transposition tables and repetition detection.
+
+/// Generate `N` pseudo-random keys from `seed` with SplitMix64, see <https://prng.di.unimi.it/splitmix64.c>.
+/// This is a `const fn` so key tables can be computed at compile time.
+pub const fn zobrist_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
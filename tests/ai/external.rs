@@ -0,0 +1,110 @@
+#![cfg(all(unix, feature = "native"))]
+
+use std::process::Command;
+use std::time::Duration;
+
+use internal_iterator::InternalIterator;
+
+use board_game::ai::external::{ExternalBoard, ExternalBot, Protocol};
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardAvailableMoves};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+
+/// A fake UAI engine that always plays `f1`.
+const FAKE_ENGINE: &str = r#"
+while read -r line; do
+    case "$line" in
+        uai) echo "id name fake"; echo "uaiok" ;;
+        isready) echo "readyok" ;;
+        go*) echo "info depth 1"; echo "bestmove f1" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+fn fake_engine(move_time: Duration) -> ExternalBot {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(FAKE_ENGINE);
+    ExternalBot::new(command, Protocol::Uai, move_time, Duration::from_secs(1)).unwrap()
+}
+
+#[test]
+fn uai_move() {
+    let mut bot = fake_engine(Duration::from_millis(10));
+    let board = AtaxxBoard::default();
+    assert_eq!(bot.select_move(&board), Move::from_uai("f1"));
+}
+
+#[test]
+fn uai_invalid_move() {
+    let mut bot = fake_engine(Duration::from_millis(10));
+    // f1 is not available for the second player
+    let board = AtaxxBoard::default().clone_and_play(Move::from_uai("f1"));
+    assert!(bot.try_select_move(&board).is_err());
+}
+
+#[test]
+fn spawn_failure() {
+    let command = Command::new("this-engine-does-not-exist");
+    let result = ExternalBot::new(
+        command,
+        Protocol::Uai,
+        Duration::from_millis(10),
+        Duration::from_secs(1),
+    );
+    assert!(result.is_err());
+}
+
+/// A fake UAI engine that always plays `f1` and writes the positions it receives to the file `$1`.
+const LOGGING_ENGINE: &str = r#"
+while read -r line; do
+    case "$line" in
+        uai) echo "uaiok" ;;
+        isready) echo "readyok" ;;
+        position*) echo "$line" >> "$1" ;;
+        go*) echo "bestmove f1" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+#[test]
+fn uai_move_history() {
+    let log = std::env::temp_dir().join(format!("board-game-external-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(LOGGING_ENGINE).arg("sh").arg(&log);
+    let mut bot = ExternalBot::new(
+        command,
+        Protocol::Uai,
+        Duration::from_millis(10),
+        Duration::from_secs(1),
+    )
+    .unwrap();
+
+    let start = AtaxxBoard::default();
+    let mv = bot.select_move(&start);
+
+    // the opponent replies, the engine should get the moves of the game instead of only the final position
+    let board = start.clone_and_play(mv);
+    let reply = board.available_moves().collect::<Vec<_>>()[0];
+    let board = board.clone_and_play(reply);
+    let _ = bot.try_select_move(&board);
+
+    // an unrelated position starts a new game
+    let other = AtaxxBoard::from_fen("x5o/7/7/7/7/7/o5x o 0 1");
+    let _ = bot.try_select_move(&other);
+
+    drop(bot);
+    let lines = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+
+    let start_fen = start.external_fen();
+    let expected = vec![
+        format!("position fen {}", start_fen),
+        format!("position fen {} moves f1 {}", start_fen, reply),
+        format!("position fen {}", other.external_fen()),
+    ];
+    assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+}
@@ -0,0 +1,42 @@
+use std::mem::size_of;
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::mcts::{mcts_build_tree_until, MCTSBot, Node};
+use board_game::board::Board;
+use board_game::games::sttt::{Coord, STTTBoard};
+
+#[test]
+fn mcts_memory_limit() {
+    let board = STTTBoard::default();
+    let limit = 64 * 1024;
+
+    let mut unlimited = MCTSBot::new(10_000, 2.0, Xoroshiro64StarStar::seed_from_u64(0));
+    assert!(unlimited.build_tree(&board).memory_bytes() > limit);
+
+    let mut limited = MCTSBot::new(10_000, 2.0, Xoroshiro64StarStar::seed_from_u64(0)).with_memory_limit(limit);
+    let tree = limited.build_tree(&board);
+    assert!(tree.memory_bytes() <= limit);
+    // the tree keeps growing until it's close to the limit
+    let used = tree.nodes.len() * size_of::<Node<Coord>>();
+    assert!(used > limit * 3 / 4, "only used {} of {} bytes", used, limit);
+    assert_eq!(10_000, tree[0].visits);
+    assert!(board.is_available_move(tree.best_move()));
+}
+
+#[test]
+fn mcts_stop_condition() {
+    let board = STTTBoard::default();
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+
+    let mut checked = vec![];
+    let tree = mcts_build_tree_until(&board, 2.0, None, &mut rng, |i| {
+        checked.push(i);
+        i >= 100
+    });
+
+    // the condition is checked before every iteration
+    assert_eq!(checked, (0..=100).collect::<Vec<u64>>());
+    assert_eq!(100, tree[0].visits);
+}
@@ -0,0 +1,54 @@
+use internal_iterator::InternalIterator;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::minimax::{minimax, minimax_incremental, IncrementalHeuristic};
+use board_game::board::{Board, BoardAvailableMoves};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::heuristic::ataxx::AtaxxSquareHeuristic;
+
+#[test]
+fn ataxx_accumulator_play_undo() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+    let heuristic = AtaxxSquareHeuristic::default();
+
+    let mut board = AtaxxBoard::default();
+    let mut acc = heuristic.accumulator(&board);
+
+    while !board.is_done() {
+        // playing and undoing every move must leave the accumulator unchanged
+        board.available_moves().for_each(|mv| {
+            let child = board.clone_and_play(mv);
+            let before = acc;
+            heuristic.play(&mut acc, &board, mv, &child);
+            assert_eq!(acc, heuristic.accumulator(&child));
+            heuristic.undo(&mut acc, &board, mv, &child);
+            assert_eq!(acc, before);
+        });
+
+        let mv = board.random_available_move(&mut rng);
+        let child = board.clone_and_play(mv);
+        heuristic.play(&mut acc, &board, mv, &child);
+        board = child;
+        assert_eq!(acc, heuristic.accumulator(&board));
+    }
+}
+
+#[test]
+fn ataxx_incremental_matches_plain() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+    let heuristic = AtaxxSquareHeuristic::default();
+
+    let mut board = AtaxxBoard::default();
+    for _ in 0..20 {
+        if board.is_done() {
+            break;
+        }
+
+        let plain = minimax(&board, &heuristic, 2, &mut rng);
+        let incremental = minimax_incremental(&board, &heuristic, 2, &mut rng);
+        assert_eq!(plain.value, incremental.value);
+
+        board.play(board.random_available_move(&mut rng));
+    }
+}
@@ -1 +1,5 @@
+pub mod external;
+pub mod mcts;
+pub mod minimax;
+pub mod simple;
 pub mod solver;
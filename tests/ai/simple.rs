@@ -0,0 +1,21 @@
+#![cfg(feature = "native")]
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::simple::RolloutBot;
+use board_game::ai::Bot;
+use board_game::games::sttt::STTTBoard;
+
+#[test]
+fn rollout_independent_of_threads() {
+    let board = STTTBoard::default();
+
+    let select = |threads: usize| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let mut bot = RolloutBot::new(1000, Xoroshiro64StarStar::seed_from_u64(0));
+        pool.install(|| bot.select_move(&board))
+    };
+
+    assert_eq!(select(1), select(4));
+}
@@ -1,4 +1,6 @@
 use internal_iterator::InternalIterator;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
 
 use board_game::board::{Board, BoardAvailableMoves, Outcome, Player};
 use board_game::games::ataxx::{AtaxxBoard, Move};
@@ -42,3 +44,48 @@ fn ataxx_forced_pass() {
     assert!(board.available_moves().all(|mv| mv == Move::Pass));
     board_test_main(&board)
 }
+
+#[test]
+fn ataxx_move_notation() {
+    let board = AtaxxBoard::default();
+    board.available_moves().for_each(|mv: Move| {
+        let s = mv.to_string();
+        assert_eq!(Ok(mv), s.parse(), "Failed to round-trip {}", s);
+    });
+
+    assert_eq!(Ok(Move::Pass), "0000".parse());
+    assert_eq!("a1g7", "a1g7".parse::<Move>().unwrap().to_string());
+
+    for invalid in ["", "a", "h1", "a8", "a0", "a1b", "0001", "a1g7a", "A1"] {
+        assert!(invalid.parse::<Move>().is_err(), "'{}' should be invalid", invalid);
+    }
+}
+
+#[test]
+fn ataxx_handicap() {
+    let board = AtaxxBoard::default();
+
+    let handicap = board.with_handicap(Player::A, 1);
+    assert_eq!(1, handicap.tiles_a().count());
+    assert_eq!(board.tiles_b(), handicap.tiles_b());
+    assert!(!handicap.is_done());
+    board_test_main(&handicap);
+
+    let lost = board.with_handicap(Player::B, 2);
+    assert_eq!(Some(Outcome::WonBy(Player::A)), lost.outcome());
+}
+
+#[test]
+fn ataxx_zobrist_incremental() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+
+    for _ in 0..10 {
+        let mut board = AtaxxBoard::default();
+        while !board.is_done() {
+            board.play(board.random_available_move(&mut rng));
+
+            assert_eq!(board.compute_zobrist(), board.zobrist());
+            assert_eq!(board.zobrist(), AtaxxBoard::from_fen(&board.to_fen()).zobrist());
+        }
+    }
+}
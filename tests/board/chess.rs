@@ -1,5 +1,6 @@
 use chess::ChessMove;
 
+use board_game::ai::external::ExternalBoard;
 use board_game::board::Board;
 use board_game::games::chess::ChessBoard;
 
@@ -25,3 +26,15 @@ fn chess_en_passant() {
 
     board_test_main(&board);
 }
+
+#[test]
+fn chess_fen_move_counters() {
+    let board = ChessBoard::external_from_fen("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1").unwrap();
+    assert_eq!((board.reversible_moves(), board.full_moves()), (1, 1));
+
+    let board = board.clone_and_play(ChessMove::from_san(board.inner(), "Nf6").unwrap());
+    assert_eq!(
+        board.external_fen(),
+        "rnbqkb1r/pppppppp/5n2/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 2 2"
+    );
+}
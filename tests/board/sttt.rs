@@ -1,5 +1,8 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
 use board_game::board::{Board, Outcome};
-use board_game::games::sttt::{board_from_compact_string, STTTBoard};
+use board_game::games::sttt::{board_from_compact_string, board_to_compact_string, STTTBoard};
 
 use crate::board::board_test_main;
 
@@ -29,3 +32,19 @@ fn sttt_draw() {
     assert_eq!(board.outcome(), Some(Outcome::Draw));
     board_test_main(&board)
 }
+
+#[test]
+fn sttt_zobrist_incremental() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+
+    for _ in 0..10 {
+        let mut board = STTTBoard::default();
+        while !board.is_done() {
+            board.play(board.random_available_move(&mut rng));
+
+            assert_eq!(board.compute_zobrist(), board.zobrist());
+            let parsed = board_from_compact_string(&board_to_compact_string(&board));
+            assert_eq!(board.zobrist(), parsed.zobrist());
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::simple::RandomBot;
+use board_game::board::{Board, Outcome, Player};
+use board_game::env::Env;
+use board_game::games::sttt::STTTBoard;
+
+#[test]
+fn sttt_random_games() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+
+    for &agent in &[Player::A, Player::B] {
+        let opponent = RandomBot::new(Xoroshiro64StarStar::seed_from_u64(1));
+        let mut env = Env::new(STTTBoard::default(), opponent, agent);
+
+        for _ in 0..10 {
+            let mut board = env.reset();
+            assert_eq!(board.next_player(), agent);
+
+            loop {
+                let (next, reward, done) = env.step(board.random_available_move(&mut rng));
+                assert_eq!(&next, env.board());
+                assert_eq!(done, next.is_done());
+
+                let expected = match next.outcome() {
+                    Some(Outcome::WonBy(player)) if player == agent => 1.0,
+                    Some(Outcome::WonBy(_)) => -1.0,
+                    _ => 0.0,
+                };
+                assert_eq!(reward, expected);
+
+                if done {
+                    break;
+                }
+                assert_eq!(next.next_player(), agent);
+                board = next;
+            }
+        }
+    }
+}
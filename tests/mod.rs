@@ -1,3 +1,8 @@
 pub mod ai;
 pub mod board;
+pub mod env;
+pub mod online;
 pub mod perft;
+pub mod server;
+pub mod uai;
+pub mod util;
@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use board_game::ai::simple::RandomBot;
+use board_game::board::Board;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::online::{play_online, ClientEvent, OnlineClient};
+use rand::thread_rng;
+
+/// A fake server that replays fixed events and records the submitted moves.
+#[derive(Debug, Default)]
+struct FakeClient {
+    events: VecDeque<ClientEvent<AtaxxBoard>>,
+    submitted: Vec<(String, Move)>,
+}
+
+impl OnlineClient<AtaxxBoard> for FakeClient {
+    fn login(&mut self) -> Result<String, String> {
+        Ok("bot".to_string())
+    }
+
+    fn next_event(&mut self) -> Result<Option<ClientEvent<AtaxxBoard>>, String> {
+        Ok(self.events.pop_front())
+    }
+
+    fn submit_move(&mut self, game_id: &str, mv: Move) -> Result<(), String> {
+        self.submitted.push((game_id.to_string(), mv));
+        Ok(())
+    }
+}
+
+#[test]
+fn plays_on_our_turn() {
+    let board = AtaxxBoard::default();
+    let state = |game_id: &str, our_turn: bool| ClientEvent::GameState {
+        game_id: game_id.to_string(),
+        board: board.clone(),
+        our_turn,
+    };
+
+    let mut client = FakeClient::default();
+    client.events.extend(vec![
+        state("a", true),
+        state("b", false),
+        state("b", true),
+        ClientEvent::GameEnd {
+            game_id: "a".to_string(),
+        },
+    ]);
+
+    let games = play_online(&mut client, || RandomBot::new(thread_rng())).unwrap();
+    assert_eq!(games, 1);
+
+    let games: Vec<&str> = client.submitted.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(games, vec!["a", "b"]);
+    assert!(client.submitted.iter().all(|&(_, mv)| board.is_available_move(mv)));
+}
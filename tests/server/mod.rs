@@ -0,0 +1,66 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+use serde_json::Value;
+
+use board_game::ai::simple::RandomBot;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::server::GameServer;
+
+fn request(server: &mut GameServer<AtaxxBoard, RandomBot<Xoroshiro64StarStar>>, request: &str) -> Value {
+    serde_json::from_str(&server.handle(request)).unwrap()
+}
+
+#[test]
+fn play_game() {
+    let bot = RandomBot::new(Xoroshiro64StarStar::seed_from_u64(0));
+    let mut server = GameServer::new(AtaxxBoard::default(), bot);
+
+    let state = request(&mut server, r#"{"id": 0, "method": "state"}"#);
+    assert_eq!(state["id"], 0);
+    assert_eq!(state["result"]["fen"], AtaxxBoard::default().to_fen());
+    assert_eq!(state["result"]["next_player"], "A");
+
+    let legal = request(&mut server, r#"{"id": 1, "method": "legal_moves"}"#);
+    assert!(legal["result"].as_array().unwrap().contains(&Value::from("f1")));
+
+    let played = request(&mut server, r#"{"id": 2, "method": "play", "params": {"move": "f1"}}"#);
+    assert_eq!(played["result"]["moves"], serde_json::json!(["f1"]));
+    assert_eq!(played["result"]["next_player"], "B");
+
+    let bot_move = request(&mut server, r#"{"id": 3, "method": "bot_move"}"#);
+    let mv = bot_move["result"]["move"].as_str().unwrap();
+    assert_eq!(bot_move["result"]["state"]["moves"], serde_json::json!(["f1", mv]));
+
+    let reset = request(&mut server, r#"{"id": 4, "method": "new_game"}"#);
+    assert_eq!(reset["result"]["moves"], serde_json::json!([]));
+}
+
+#[test]
+fn errors() {
+    let bot = RandomBot::new(Xoroshiro64StarStar::seed_from_u64(0));
+    let mut server = GameServer::new(AtaxxBoard::default(), bot);
+
+    assert!(request(&mut server, "not json")["error"].is_string());
+    assert!(request(&mut server, r#"{"id": 0, "method": "fly"}"#)["error"].is_string());
+    assert!(request(&mut server, r#"{"id": 0, "method": "play", "params": {"move": "a1"}}"#)["error"].is_string());
+    assert!(request(&mut server, r#"{"id": 0, "method": "play", "params": {"move": "zz"}}"#)["error"].is_string());
+    assert!(request(&mut server, r#"{"id": 0, "method": "play"}"#)["error"].is_string());
+
+    // the failed requests did not change anything
+    assert_eq!(server.board(), &AtaxxBoard::default());
+}
+
+#[test]
+fn run_lines() {
+    let bot = RandomBot::new(Xoroshiro64StarStar::seed_from_u64(0));
+    let mut server = GameServer::new(AtaxxBoard::default(), bot);
+
+    let input = "{\"id\": 1, \"method\": \"state\"}\n\n{\"id\": 2, \"method\": \"bot_move\"}\n";
+    let mut output = vec![];
+    server.run(input.as_bytes(), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains("\"id\":2"));
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "native")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use internal_iterator::InternalIterator;
+
+use board_game::ai::Bot;
+use board_game::board::BoardAvailableMoves;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::uai::client::run_bot;
+
+/// A bot that plays the first available move and records the time it is given for each move.
+#[derive(Debug, Default)]
+struct RecordTimeBot {
+    times: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Bot<AtaxxBoard> for RecordTimeBot {
+    fn select_move(&mut self, board: &AtaxxBoard) -> Move {
+        board.available_moves().collect::<Vec<_>>()[0]
+    }
+
+    fn select_move_timed(&mut self, board: &AtaxxBoard, time: Duration) -> (Move, Option<f32>) {
+        self.times.lock().unwrap().push(time);
+        (self.select_move(board), None)
+    }
+}
+
+#[test]
+fn run_bot_time() {
+    let bot = RecordTimeBot::default();
+    let times = Arc::clone(&bot.times);
+
+    let input = "\
+        position startpos\n\
+        go movetime 1000\n\
+        position startpos moves f1\n\
+        go wtime 60000 btime 30000 winc 1000 binc 500\n\
+        quit\n";
+    let mut output = vec![];
+    run_bot(bot, "test", "test", input.as_bytes(), &mut output, std::io::sink()).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.lines().filter(|line| line.starts_with("bestmove ")).count(), 2);
+
+    // the first call is the warmup, then 90% of the move time and of clock / 30 + increment for the second player
+    let expected: Vec<Duration> = vec![1000, 900, 1350].into_iter().map(Duration::from_millis).collect();
+    assert_eq!(*times.lock().unwrap(), expected);
+}
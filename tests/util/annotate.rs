@@ -0,0 +1,44 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::board::Board;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::util::annotate::annotate;
+use board_game::util::pgn::parse_pgn;
+use board_game::util::replay::Replay;
+
+#[test]
+fn ataxx_blunder() {
+    let rng = Xoroshiro64StarStar::seed_from_u64(0);
+    let mut bot = MCTSBot::new(100, 2.0, rng);
+
+    // x can capture the last o tile, but instead jumps away and gets captured itself
+    let start = AtaxxBoard::from_fen("6o/7/6x/7/7/7/7 x 0 1");
+    let mut game = Replay::new(start.clone());
+    game.push(Move::from_uai("g5e5"), vec![("time".to_string(), "0.1".to_string())]);
+
+    let annotated = annotate(&game, &mut bot, 1000);
+    assert_eq!(annotated.moves.len(), 1);
+
+    let mv = &annotated.moves[0];
+    assert_eq!(mv.mv, Move::from_uai("g5e5"));
+    assert_eq!(mv.annotation("time"), Some("0.1"));
+    assert_eq!(mv.annotation("eval"), Some("-1.000"));
+    assert_eq!(mv.annotation("best_eval"), Some("1.000"));
+    assert_eq!(mv.annotation("blunder"), Some("1"));
+
+    // the best move wins immediately
+    let best = mv.annotation("best").unwrap().split(',').next().unwrap();
+    assert!(start.clone_and_play(best.parse().unwrap()).is_done());
+
+    let json = annotated.to_json();
+    assert_eq!(json["moves"][0]["move"], "g5e5");
+    assert_eq!(json["moves"][0]["blunder"], "1");
+    assert!(json["outcome"].is_null());
+
+    let pgn = annotated.to_pgn(vec![], |_, mv| mv.to_string());
+    let parsed = parse_pgn(&pgn.to_string()).unwrap();
+    assert_eq!(parsed, vec![pgn]);
+    assert!(parsed[0].comments[0].as_ref().unwrap().contains("blunder=1"));
+}
@@ -0,0 +1,39 @@
+use board_game::util::arena::Arena;
+
+#[test]
+fn arena_chunks() {
+    let mut arena = Arena::new();
+    assert!(arena.is_empty());
+
+    assert_eq!(arena.push(0), 0);
+    let first: *const usize = &arena[0];
+
+    // enough elements to need multiple chunks
+    let count = 200_000;
+    for i in 1..count {
+        assert_eq!(arena.push(i), i);
+    }
+
+    assert_eq!(arena.len(), count);
+    assert!(arena.capacity() >= count);
+    assert!(arena.iter().copied().eq(0..count));
+    assert_eq!(first, &arena[0] as *const usize, "elements should never move");
+
+    arena[count - 1] = 5;
+    assert_eq!(arena.get(count - 1), Some(&5));
+    assert_eq!(arena.get(count), None);
+}
+
+#[test]
+fn arena_max_len() {
+    let mut arena = Arena::with_max_len(1000);
+    for i in 0..1000 {
+        arena.push(i);
+    }
+    // the last chunk is only as large as needed to reach the maximum length
+    assert_eq!(arena.capacity(), 1000);
+
+    // pushing more still works
+    arena.push(1000);
+    assert!(arena.iter().copied().eq(0..1001));
+}
@@ -0,0 +1,83 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use board_game::games::ataxx::{Coord, Tiles};
+use board_game::util::bitboard::{BitBoard, Bits, Wide};
+
+/// The cells at exactly king distance `distance` from a set cell, computed one cell at a time.
+fn reference<T: Bits, const W: u8, const H: u8>(board: BitBoard<T, W, H>, distance: i32) -> BitBoard<T, W, H> {
+    let mut result = BitBoard::empty();
+    for (x, y) in board.iter() {
+        for dx in -distance..=distance {
+            for dy in -distance..=distance {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                let on_board = nx >= 0 && ny >= 0 && nx < W as i32 && ny < H as i32;
+                if on_board && dx.abs().max(dy.abs()) == distance {
+                    result = result.set(nx as u8, ny as u8);
+                }
+            }
+        }
+    }
+    result
+}
+
+fn random_board<T: Bits, const W: u8, const H: u8>(rng: &mut impl Rng) -> BitBoard<T, W, H> {
+    let mut board = BitBoard::empty();
+    for _ in 0..rng.gen_range(0..6) {
+        board = board.set(rng.gen_range(0..W), rng.gen_range(0..H));
+    }
+    board
+}
+
+fn check_neighbors<T: Bits, const W: u8, const H: u8>(rng: &mut impl Rng) {
+    for _ in 0..200 {
+        let board: BitBoard<T, W, H> = random_board(rng);
+        assert_eq!(reference(board, 1), board.adjacent(), "\n{}", board);
+        assert_eq!(reference(board, 2), board.distance_two(), "\n{}", board);
+        assert_eq!((!board).count() + board.count(), W as u32 * H as u32);
+    }
+}
+
+#[test]
+fn bitboard_neighbors() {
+    let mut rng = StdRng::seed_from_u64(0);
+    check_neighbors::<u64, 7, 7>(&mut rng);
+    check_neighbors::<u64, 8, 8>(&mut rng);
+    check_neighbors::<u128, 10, 10>(&mut rng);
+    check_neighbors::<u128, 11, 11>(&mut rng);
+    check_neighbors::<Wide<2>, 10, 10>(&mut rng);
+    check_neighbors::<Wide<3>, 13, 13>(&mut rng);
+}
+
+#[test]
+fn bitboard_matches_ataxx_tiles() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let board: BitBoard<u64, 7, 7> = random_board(&mut rng);
+        let tiles = board
+            .iter()
+            .fold(Tiles::empty(), |t, (x, y)| t.set(Coord::from_xy(x, y)));
+
+        let to_board = |tiles: Tiles| tiles.into_iter().fold(BitBoard::empty(), |b, c| b.set(c.x(), c.y()));
+        assert_eq!(to_board(tiles.copy_targets()), board.adjacent());
+        assert_eq!(to_board(tiles.jump_targets()), board.distance_two());
+    }
+}
+
+#[test]
+fn bitboard_basics() {
+    type Board = BitBoard<Wide<2>, 10, 10>;
+    let board = Board::coord(9, 9).set(0, 0).set(3, 4);
+    assert_eq!(board.count(), 3);
+    assert!(board.has(3, 4) && !board.has(4, 3));
+    assert_eq!(board.iter().collect::<Vec<_>>(), vec![(0, 0), (3, 4), (9, 9)]);
+    assert_eq!(board.clear(0, 0).count(), 2);
+
+    // nothing wraps around the edges
+    assert_eq!(Board::coord(9, 0).right(), Board::empty());
+    assert_eq!(Board::coord(0, 5).left(), Board::empty());
+    assert_eq!(Board::coord(5, 9).up(), Board::empty());
+    assert_eq!(Board::coord(5, 0).down(), Board::empty());
+    assert_eq!(Board::full().count(), 100);
+    assert!((!Board::full()).is_empty());
+}
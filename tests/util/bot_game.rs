@@ -0,0 +1,652 @@
+#![cfg(feature = "native")]
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::simple::RandomBot;
+use board_game::ai::Bot;
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::sttt::STTTBoard;
+use board_game::util::bot_game::{
+    game_seeds, parse_openings, run, run_conditions, run_observed, run_openings, run_recorded, run_seeded, run_sprt,
+    run_with, Adjudication, BotGameResult, Condition, GameEvent, GameSummary, MatchOptions, TimeControl,
+};
+use board_game::util::elo::{Hypothesis, Sprt};
+use board_game::util::replay::{parse_replays, Replay};
+
+#[test]
+fn observer_events() {
+    let events = Mutex::new(vec![]);
+
+    let result = run_observed(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        4,
+        false,
+        |event: &GameEvent<AtaxxBoard>| {
+            let kind = match event {
+                GameEvent::Start { .. } => 's',
+                GameEvent::Move { value, .. } => {
+                    assert_eq!(*value, None);
+                    'm'
+                }
+                GameEvent::End { .. } => 'e',
+            };
+            events.lock().unwrap().push(kind);
+        },
+    );
+
+    let events = events.into_inner().unwrap();
+    assert_eq!(events.iter().filter(|&&c| c == 's').count(), 4);
+    assert_eq!(events.iter().filter(|&&c| c == 'e').count(), 4);
+
+    let moves = events.iter().filter(|&&c| c == 'm').count();
+    assert_eq!(moves as f32, result.game_length * result.game_count as f32);
+}
+
+#[test]
+fn bots_built_per_game() {
+    let built = AtomicU32::new(0);
+    let build = || {
+        built.fetch_add(1, Ordering::Relaxed);
+        RandomBot::new(thread_rng())
+    };
+
+    let result = run(AtaxxBoard::default, build, build, 2, true);
+
+    // one bot per side per game, none extra for the debug strings
+    assert_eq!(built.load(Ordering::Relaxed), 2 * result.game_count);
+    assert!(result.debug_l.starts_with("RandomBot"));
+}
+
+#[test]
+fn sprt_stops_early() {
+    // random against itself never reaches the maximum number of games for a hypothesis this far off
+    let sprt = Sprt::new(400.0, 800.0, 0.05, 0.05);
+    let result = run_sprt(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        1000,
+        true,
+        sprt,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    let sprt_result = result.sprt.unwrap();
+    assert_eq!(sprt_result.accepted, Some(Hypothesis::H0));
+    assert!(result.game_count < 2000);
+    assert!(sprt_result.llr.len() as u32 <= result.game_count);
+    assert!(*sprt_result.llr.last().unwrap() <= sprt.bounds().0);
+}
+
+#[test]
+fn sprt_stops_early_lopsided() {
+    // mcts wins (nearly) every game against random, which should be detected quickly
+    let sprt = Sprt::new(0.0, 100.0, 0.05, 0.05);
+    let result = run_sprt(
+        STTTBoard::default,
+        || MCTSBot::new(200, 2.0, thread_rng()),
+        || RandomBot::new(thread_rng()),
+        500,
+        false,
+        sprt,
+        |_: &GameEvent<STTTBoard>| {},
+    );
+
+    let sprt_result = result.sprt.unwrap();
+    assert_eq!(sprt_result.accepted, Some(Hypothesis::H1));
+    assert!(result.game_count < 100, "took {} games", result.game_count);
+    assert!(*sprt_result.llr.last().unwrap() >= sprt.bounds().1);
+}
+
+#[test]
+fn both_sides_game_count() {
+    let result = run(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        3,
+        true,
+    );
+    assert_eq!(result.game_count, 6);
+    assert!(result.sprt.is_none());
+}
+
+#[test]
+fn openings_mirrored() {
+    let openings: Vec<AtaxxBoard> =
+        parse_openings("# suite\nx5o/7/7/7/7/7/o5x x 0 1\n\nx5o/7/3-3/7/7/7/o5x o 0 1\n").unwrap();
+    assert_eq!(openings.len(), 2);
+
+    let starts = Mutex::new(vec![]);
+    let result = run_openings(
+        &openings,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        |event: &GameEvent<AtaxxBoard>| {
+            if let GameEvent::Start { game_id, board } = *event {
+                starts.lock().unwrap().push((game_id, board.clone()));
+            }
+        },
+    );
+    assert_eq!(result.game_count, 4);
+
+    let mut starts = starts.into_inner().unwrap();
+    starts.sort_by_key(|&(game_id, _)| game_id);
+    let starts: Vec<AtaxxBoard> = starts.into_iter().map(|(_, board)| board).collect();
+    assert_eq!(
+        starts,
+        vec![
+            openings[0].clone(),
+            openings[0].clone(),
+            openings[1].clone(),
+            openings[1].clone()
+        ]
+    );
+
+    assert!(parse_openings::<AtaxxBoard>("not a fen").is_err());
+}
+
+#[test]
+fn time_control_budget() {
+    let tc = TimeControl::per_game(Duration::from_secs(60), Duration::from_secs(1));
+    let budget = tc.budget(Some(Duration::from_secs(60))).unwrap();
+    assert!(budget > Duration::from_secs(2) && budget < Duration::from_secs(3));
+    assert!(tc.budget(Some(Duration::from_millis(10))).unwrap() < Duration::from_millis(10));
+
+    let tc = TimeControl::per_move(Duration::from_millis(100));
+    let budget = tc.budget(None).unwrap();
+    assert!(budget > Duration::from_millis(85) && budget < Duration::from_millis(95));
+}
+
+#[test]
+fn time_forfeit() {
+    #[derive(Debug)]
+    struct SlowBot;
+    impl Bot<AtaxxBoard> for SlowBot {
+        fn select_move(&mut self, board: &AtaxxBoard) -> <AtaxxBoard as Board>::Move {
+            std::thread::sleep(Duration::from_millis(50));
+            board.random_available_move(&mut thread_rng())
+        }
+    }
+
+    let options = MatchOptions {
+        time_control: Some(TimeControl::per_move(Duration::from_millis(20))),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || SlowBot,
+        || RandomBot::new(thread_rng()),
+        2,
+        true,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    // the slow bot loses every game on its first move
+    assert_eq!(result.game_count, 4);
+    assert_eq!(result.time_forfeits_l, 4);
+    assert_eq!(result.time_forfeits_r, 0);
+    assert_eq!(result.wins_r, 4);
+}
+
+#[test]
+fn mcts_timed() {
+    let mut bot = MCTSBot::new(u64::MAX, 2.0, thread_rng());
+    let start = Instant::now();
+    let (_, value) = bot.select_move_timed(&AtaxxBoard::default(), Duration::from_millis(50));
+    let elapsed = Instant::now() - start;
+
+    assert!(value.is_some());
+    // only a loose bound, the stop condition itself is tested by mcts_stop_condition
+    assert!(elapsed < Duration::from_secs(5));
+}
+
+#[test]
+fn recorded_games() {
+    let (result, output) = run_recorded(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || MCTSBot::new(10, 2.0, thread_rng()),
+        2,
+        true,
+        &MatchOptions::default(),
+        vec![],
+    );
+
+    let replays: Vec<Replay<AtaxxBoard>> = parse_replays(&String::from_utf8(output.unwrap()).unwrap()).unwrap();
+    assert_eq!(replays.len() as u32, result.game_count);
+
+    let moves: usize = replays.iter().map(|r| r.moves.len()).sum();
+    assert_eq!(moves as f32, result.game_length * result.game_count as f32);
+    for replay in &replays {
+        assert!(replay.end().is_done());
+        for mv in &replay.moves {
+            assert!(mv.annotation("time").is_some());
+            let mcts = mv.annotation("bot") == Some("r");
+            assert_eq!(mv.annotation("value").is_some(), mcts);
+        }
+    }
+}
+
+#[test]
+fn result_channel() {
+    let (sender, receiver) = channel();
+    let options = MatchOptions {
+        results: Some(sender),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        3,
+        true,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+    drop(options);
+
+    let mut summaries: Vec<GameSummary<AtaxxBoard>> = receiver.iter().collect();
+    summaries.sort_by_key(|s| s.game_id);
+    assert_eq!(summaries.len() as u32, result.game_count);
+
+    for (i, summary) in summaries.iter().enumerate() {
+        assert_eq!(summary.game_id, i as u32);
+        assert_eq!(summary.left_first, i % 2 == 0);
+        assert_eq!(summary.start, AtaxxBoard::default());
+        assert!(summary.bot_l.starts_with("RandomBot"));
+        assert!(!summary.forfeit);
+        assert!(summary.panic.is_none());
+    }
+
+    let wins_l = summaries
+        .iter()
+        .filter(|s| {
+            let player_l = if s.left_first { Player::A } else { Player::B };
+            s.outcome == Some(Outcome::WonBy(player_l))
+        })
+        .count();
+    assert_eq!(wins_l as u32, result.wins_l);
+    let moves: u32 = summaries.iter().map(|s| s.move_count).sum();
+    assert_eq!(moves as f32, result.game_length * result.game_count as f32);
+}
+
+#[test]
+fn seeded_games_reproducible() {
+    let play = |seed: u64| {
+        let games = Mutex::new(BTreeMap::new());
+        let result = run_seeded(
+            AtaxxBoard::default,
+            |seed| RandomBot::new(StdRng::seed_from_u64(seed)),
+            |seed| RandomBot::new(StdRng::seed_from_u64(seed)),
+            3,
+            true,
+            seed,
+            &MatchOptions::default(),
+            |event: &GameEvent<AtaxxBoard>| {
+                if let GameEvent::Move { game_id, mv, .. } = *event {
+                    games.lock().unwrap().entry(game_id).or_insert_with(Vec::new).push(mv);
+                }
+            },
+        );
+        (result.wins_l, games.into_inner().unwrap())
+    };
+
+    let (wins_a, games_a) = play(5);
+    let (wins_b, games_b) = play(5);
+    assert_eq!(wins_a, wins_b);
+    assert_eq!(games_a, games_b);
+
+    let (_, games_c) = play(6);
+    assert_ne!(games_a, games_c);
+
+    let (seed_l, seed_r) = game_seeds(5, 0);
+    assert_ne!(seed_l, seed_r);
+    assert_ne!(game_seeds(5, 0), game_seeds(5, 1));
+}
+
+#[derive(Debug)]
+struct PanicBot(u32);
+
+impl Bot<AtaxxBoard> for PanicBot {
+    fn select_move(&mut self, board: &AtaxxBoard) -> <AtaxxBoard as Board>::Move {
+        self.0 += 1;
+        if self.0 == 3 {
+            panic!("third move");
+        }
+        board.random_available_move(&mut thread_rng())
+    }
+}
+
+#[test]
+fn panics_caught() {
+    let (sender, receiver) = channel();
+    let options = MatchOptions {
+        catch_panics: true,
+        results: Some(sender),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || PanicBot(0),
+        || RandomBot::new(thread_rng()),
+        2,
+        true,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    assert_eq!(result.game_count, 0);
+    assert_eq!(result.panics.len(), 4);
+
+    // without any finished games the statistics are still defined
+    assert_eq!(result.game_length, 0.0);
+    assert_eq!(
+        (result.win_rate_l, result.draw_rate, result.win_rate_r),
+        (0.0, 0.0, 0.0)
+    );
+    assert_eq!(result.elo_l, 0.0);
+    assert_eq!(result.los_l, 0.5);
+    assert!(!result.time_l.is_nan() && !result.time_r.is_nan());
+    for panicked in &result.panics {
+        assert_eq!(panicked.message, "third move");
+        // two moves of the panicking bot and two or three of the other one
+        let expected = if panicked.left_first { 4 } else { 5 };
+        assert_eq!(panicked.moves.len(), expected);
+    }
+
+    // panicked games are reported on the results channel as well
+    drop(options);
+    let summaries: Vec<GameSummary<AtaxxBoard>> = receiver.iter().collect();
+    assert_eq!(summaries.len(), 4);
+    for summary in &summaries {
+        assert_eq!(summary.outcome, None);
+        assert_eq!(summary.panic.as_ref().unwrap().message, "third move");
+    }
+}
+
+#[test]
+#[should_panic(expected = "third move")]
+fn panics_propagate() {
+    let _ = run(
+        AtaxxBoard::default,
+        || PanicBot(0),
+        || RandomBot::new(thread_rng()),
+        1,
+        false,
+    );
+}
+
+#[test]
+fn adjudicate_max_moves() {
+    let options = MatchOptions {
+        adjudication: Some(Adjudication {
+            max_moves: Some(6),
+            ..Adjudication::default()
+        }),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        3,
+        false,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    assert_eq!(result.adjudicated, 3);
+    assert_eq!(result.draws, 3);
+    assert_eq!(result.game_length, 6.0);
+}
+
+#[test]
+fn adjudicate_win() {
+    // both bots claim the first player is winning
+    #[derive(Debug)]
+    struct ConfidentBot;
+    impl Bot<AtaxxBoard> for ConfidentBot {
+        fn select_move(&mut self, board: &AtaxxBoard) -> <AtaxxBoard as Board>::Move {
+            board.random_available_move(&mut thread_rng())
+        }
+        fn select_move_with_value(&mut self, board: &AtaxxBoard) -> (<AtaxxBoard as Board>::Move, Option<f32>) {
+            let value = if board.next_player() == Player::A { 0.95 } else { -0.95 };
+            (self.select_move(board), Some(value))
+        }
+    }
+
+    let options = MatchOptions {
+        adjudication: Some(Adjudication {
+            win: Some((0.9, 4)),
+            ..Adjudication::default()
+        }),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || ConfidentBot,
+        || ConfidentBot,
+        2,
+        true,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    assert_eq!(result.adjudicated, 4);
+    assert_eq!(result.game_length, 4.0);
+    // each bot played player A once
+    assert_eq!((result.wins_l, result.wins_r), (2, 2));
+}
+
+#[derive(Debug)]
+struct ScriptBot(Vec<&'static str>, usize);
+
+impl Bot<AtaxxBoard> for ScriptBot {
+    fn select_move(&mut self, _: &AtaxxBoard) -> <AtaxxBoard as Board>::Move {
+        let mv = self.0[self.1 % self.0.len()].parse().unwrap();
+        self.1 += 1;
+        mv
+    }
+}
+
+#[test]
+fn adjudicate_repetition() {
+    // both players jump back and forth
+    let options = MatchOptions {
+        adjudication: Some(Adjudication {
+            repetitions: Some((3, AtaxxBoard::zobrist)),
+            ..Adjudication::default()
+        }),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        || AtaxxBoard::from_fen("x5o/7/7/7/7/7/7 x 0 1"),
+        || ScriptBot(vec!["a7c7", "c7a7"], 0),
+        || ScriptBot(vec!["g7e7", "e7g7"], 0),
+        1,
+        false,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    assert_eq!(result.adjudicated, 1);
+    assert_eq!(result.draws, 1);
+    assert_eq!(result.game_length, 8.0);
+}
+
+#[test]
+fn status_file() {
+    let path = std::env::temp_dir().join(format!("board_game_status_{}.json", std::process::id()));
+    let options = MatchOptions {
+        status_file: Some(path.clone()),
+        ..MatchOptions::default()
+    };
+    let result = run_with(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        3,
+        true,
+        &options,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    let status: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(status["games"], 6);
+    assert_eq!(status["max_games"], 6);
+    assert_eq!(status["wins_l"], result.wins_l);
+    assert_eq!(status["draws"], result.draws);
+    assert_eq!(status["wins_r"], result.wins_r);
+}
+
+#[test]
+fn result_export() {
+    let result = run(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        2,
+        true,
+    );
+
+    let json = result.to_json();
+    assert_eq!(json["game_count"], 4);
+    assert_eq!(json["wins_l"], result.wins_l);
+    assert_eq!(json["bot_l"], result.debug_l);
+    assert_eq!(json["panics"], serde_json::json!([]));
+    assert!(json["sprt"].is_null());
+
+    let header: Vec<&str> = BotGameResult::CSV_HEADER.split(',').collect();
+    let row = result.to_csv_row();
+    assert!(row.starts_with("\"RandomBot"));
+    // the debug strings don't contain commas, so splitting the row is fine here
+    let fields: Vec<&str> = row.split(',').collect();
+    assert_eq!(header.len(), fields.len());
+    assert_eq!(fields[header.iter().position(|&h| h == "game_count").unwrap()], "4");
+}
+
+#[test]
+fn handicap_conditions() {
+    let start = AtaxxBoard::default();
+    let conditions = [
+        Condition::even("even", start.clone()),
+        Condition::handicap("no tiles", &start, |board, player| board.with_handicap(player, 2)),
+    ];
+    let played = Mutex::new(vec![]);
+
+    let results = run_conditions(
+        &conditions,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        3,
+        &MatchOptions::default(),
+        |event: &GameEvent<AtaxxBoard>| {
+            if let GameEvent::Start { board, .. } = event {
+                played.lock().unwrap().push((*board).clone());
+            }
+        },
+    );
+
+    assert_eq!(2, results.len());
+    assert_eq!(6, results[0].game_count);
+
+    // the left bot starts without tiles in every game, so it loses all of them, whichever side it plays
+    let handicap = &results[1];
+    assert_eq!(6, handicap.game_count);
+    assert_eq!(6, handicap.wins_r);
+    let played = played.into_inner().unwrap();
+    assert!(played.contains(&conditions[1].start_l_first));
+    assert!(played.contains(&conditions[1].start_r_first));
+}
+
+#[test]
+fn pentanomial_pairs() {
+    let result = run(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        5,
+        true,
+    );
+
+    let pairs = result.pairs.unwrap();
+    assert_eq!(pairs.iter().sum::<u32>(), 5);
+    let half_points: u32 = pairs.iter().enumerate().map(|(i, &c)| i as u32 * c).sum();
+    assert_eq!(half_points, 2 * result.wins_l + result.draws);
+    assert!(result.pentanomial_significance().is_some());
+
+    let single = run(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        2,
+        false,
+    );
+    assert_eq!(single.pairs, None);
+}
+
+#[derive(Debug)]
+struct SlowFirstMoveBot(bool);
+
+impl Bot<AtaxxBoard> for SlowFirstMoveBot {
+    fn select_move(&mut self, board: &AtaxxBoard) -> <AtaxxBoard as Board>::Move {
+        if !self.0 {
+            self.0 = true;
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        board.random_available_move(&mut thread_rng())
+    }
+}
+
+#[test]
+fn move_time_percentiles() {
+    let result = run(
+        AtaxxBoard::default,
+        || SlowFirstMoveBot(false),
+        || RandomBot::new(thread_rng()),
+        2,
+        false,
+    );
+
+    // the slow moves barely change the median but show up in the maximum
+    let times = result.move_times_l;
+    assert!(times.max >= 0.1, "{:?}", times);
+    assert!(times.p50 < 0.05, "{:?}", times);
+    assert!(times.p50 <= times.p95 && times.p95 <= times.max);
+    assert!(result.move_times_r.max < 0.1, "{:?}", result.move_times_r);
+}
+
+#[test]
+fn sprt_complete_pairs() {
+    let sprt = Sprt::new(400.0, 800.0, 0.05, 0.05);
+    let result = run_sprt(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        1000,
+        true,
+        sprt,
+        |_: &GameEvent<AtaxxBoard>| {},
+    );
+
+    // every started opening was played with both colors, even though the test stopped early
+    assert!(result.game_count < 2000);
+    let pairs = result.pairs.unwrap();
+    assert_eq!(2 * pairs.iter().sum::<u32>(), result.game_count);
+    assert_eq!(result.sprt.unwrap().accepted, Some(Hypothesis::H0));
+}
@@ -0,0 +1,102 @@
+use board_game::util::elo::{
+    elo_estimate, elo_from_score, likelihood_of_superiority, pentanomial_significance, score_from_elo,
+    trinomial_significance, Hypothesis, Sprt,
+};
+
+#[test]
+fn elo_score_roundtrip() {
+    assert_eq!(elo_from_score(0.5), 0.0);
+    for &elo in &[-400.0, -50.0, 0.0, 120.0, 800.0] {
+        assert!((elo_from_score(score_from_elo(elo)) - elo).abs() < 0.01);
+    }
+}
+
+#[test]
+fn elo_estimate_even() {
+    let est = elo_estimate(40, 20, 40);
+    assert_eq!(est.elo, 0.0);
+    assert!((est.los - 0.5).abs() < 1e-6);
+    assert!(est.error > 0.0 && est.error.is_finite());
+
+    // more games give a smaller interval
+    let more = elo_estimate(400, 200, 400);
+    assert!(more.error < est.error);
+}
+
+#[test]
+fn elo_estimate_known() {
+    // 60 wins, 20 draws and 20 losses is a score of 0.7
+    let est = elo_estimate(60, 20, 20);
+    assert!((est.elo - 147.2).abs() < 0.1);
+    assert!((est.error - 66.0).abs() < 0.5, "error {}", est.error);
+    assert!(est.los > 0.999);
+}
+
+#[test]
+fn elo_estimate_one_sided() {
+    let est = elo_estimate(10, 0, 0);
+    assert_eq!(est.elo, f32::INFINITY);
+    assert_eq!(est.error, f32::INFINITY);
+}
+
+#[test]
+fn elo_estimate_no_games() {
+    let est = elo_estimate(0, 0, 0);
+    assert_eq!(est.elo, 0.0);
+    assert_eq!(est.error, f32::INFINITY);
+    assert_eq!(est.los, 0.5);
+}
+
+#[test]
+fn los_symmetric() {
+    assert_eq!(likelihood_of_superiority(0, 0), 0.5);
+    let a = likelihood_of_superiority(12, 8);
+    let b = likelihood_of_superiority(8, 12);
+    assert!((a + b - 1.0).abs() < 1e-6);
+    assert!((a - 0.814).abs() < 0.001, "los {}", a);
+}
+
+#[test]
+fn sprt_bounds_and_llr() {
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    let (lower, upper) = sprt.bounds();
+    assert!((lower + 2.944).abs() < 0.001);
+    assert!((upper - 2.944).abs() < 0.001);
+
+    // a clearly stronger player pushes towards H1, a clearly weaker one towards H0
+    assert_eq!(sprt.llr(0, 0, 0), 0.0);
+    assert!(sprt.llr(600, 200, 200) > upper);
+    assert_eq!(sprt.decide(sprt.llr(600, 200, 200)), Some(Hypothesis::H1));
+    assert_eq!(sprt.decide(sprt.llr(200, 200, 600)), Some(Hypothesis::H0));
+    assert_eq!(sprt.decide(sprt.llr(10, 10, 10)), None);
+}
+
+#[test]
+fn significance_small_sample() {
+    // 55% from 40 games is far from significant
+    let sig = trinomial_significance(18, 8, 14);
+    assert!((sig.score - 0.55).abs() < 1e-6);
+    assert!(sig.p_value > 0.4 && sig.p_value < 0.7, "{:?}", sig);
+    assert!(sig.los > 0.5 && sig.los < 0.8, "{:?}", sig);
+
+    // the same score from 4000 games is
+    let sig = trinomial_significance(1800, 800, 1400);
+    assert!(sig.p_value < 1e-6, "{:?}", sig);
+    assert!(sig.los > 0.999);
+
+    let even = trinomial_significance(0, 0, 0);
+    assert_eq!((even.p_value, even.los), (1.0, 0.5));
+    let perfect = trinomial_significance(10, 0, 0);
+    assert_eq!((perfect.p_value, perfect.los), (0.0, 1.0));
+}
+
+#[test]
+fn significance_pentanomial() {
+    // balanced pairs have a lower variance than the same games counted independently
+    let pairs = pentanomial_significance([2, 5, 30, 8, 5]);
+    // 2*0 + 5*1 + 30*2 + 8*3 + 5*4 = 109 half points out of 200
+    let games = trinomial_significance(34, 41, 25);
+    assert!((pairs.score - games.score).abs() < 1e-6);
+    assert!(pairs.p_value < games.p_value, "{:?} {:?}", pairs, games);
+    assert!(pairs.los > 0.5);
+}
@@ -0,0 +1,13 @@
+pub mod annotate;
+pub mod arena;
+pub mod bitboard;
+pub mod bot_game;
+pub mod elo;
+pub mod pgn;
+pub mod rating;
+pub mod render;
+pub mod replay;
+pub mod sgf;
+pub mod spectator;
+pub mod tournament;
+pub mod websocket;
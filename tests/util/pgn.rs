@@ -0,0 +1,63 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::util::pgn::{parse_pgn, PgnGame};
+
+#[test]
+fn ataxx_round_trip() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+    let start = AtaxxBoard::default();
+
+    let mut board = start.clone();
+    let mut moves = vec![];
+    while !board.is_done() {
+        let mv = board.random_available_move(&mut rng);
+        board.play(mv);
+        moves.push(mv);
+    }
+
+    let tags = vec![("Event".to_string(), "test \"quoted\"".to_string())];
+    let game = PgnGame::from_game(&start, &moves, tags, |_, mv| mv.to_uai());
+    assert_eq!(game.result, board.outcome());
+
+    let text = game.to_string();
+    println!("{}", text);
+    assert!(text.lines().all(|line| line.len() < 80));
+
+    let parsed = parse_pgn(&text).unwrap();
+    assert_eq!(parsed, vec![game.clone()]);
+
+    let replayed = parsed[0].replay(&start, |_, s| Some(Move::from_uai(s))).unwrap();
+    assert_eq!(replayed, moves);
+}
+
+#[test]
+fn tolerant_parse() {
+    let input = r#"
+[Event "first"]
+[Result "0-1"]
+
+1. a1a3 {a comment} 1... g2! (1... g1 2. b2) 2. c1?! $14 ; line comment
+0-1
+
+[Event "second"]
+[Result "1/2-1/2"]
+
+12... b2 13. b3
+"#;
+
+    let games = parse_pgn(input).unwrap();
+    assert_eq!(games.len(), 2);
+
+    assert_eq!(games[0].tag("Event"), Some("first"));
+    assert_eq!(games[0].moves, vec!["a1a3", "g2", "c1"]);
+    assert_eq!(games[0].result, Some(Outcome::WonBy(Player::B)));
+    assert_eq!(games[0].start_player, Player::A);
+
+    // missing result token falls back to the tag
+    assert_eq!(games[1].moves, vec!["b2", "b3"]);
+    assert_eq!(games[1].result, Some(Outcome::Draw));
+    assert_eq!(games[1].start_player, Player::B);
+}
@@ -0,0 +1,74 @@
+use board_game::util::rating::{elo_update, Glicko2, Glicko2Rating, Ladder};
+
+#[test]
+fn glicko2_paper_example() {
+    // the example from section 3 of http://www.glicko.net/glicko/glicko2.pdf
+    let player = Glicko2Rating {
+        rating: 1500.0,
+        deviation: 200.0,
+        volatility: 0.06,
+    };
+    let opponent = |rating, deviation| Glicko2Rating {
+        rating,
+        deviation,
+        volatility: 0.06,
+    };
+    let results = [
+        (opponent(1400.0, 30.0), 1.0),
+        (opponent(1550.0, 100.0), 0.0),
+        (opponent(1700.0, 300.0), 0.0),
+    ];
+
+    let updated = Glicko2::new(0.5).update(player, &results);
+    assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+    assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+    assert!((updated.volatility - 0.05999).abs() < 1e-5, "{:?}", updated);
+}
+
+#[test]
+fn glicko2_inactive() {
+    let player = Glicko2Rating::default();
+    let updated = Glicko2::default().update(player, &[]);
+    assert_eq!(updated.rating, player.rating);
+    assert!(updated.deviation > player.deviation);
+}
+
+#[test]
+fn elo_update_symmetric() {
+    let (a, b) = elo_update(1500.0, 1500.0, 1.0, 32.0);
+    assert_eq!((a, b), (1516.0, 1484.0));
+
+    let (a, b) = elo_update(1600.0, 1400.0, 0.5, 32.0);
+    assert!(a < 1600.0 && b > 1400.0);
+    assert!((a + b - 3000.0).abs() < 1e-9);
+}
+
+#[test]
+fn ladder_roundtrip() {
+    let mut ladder = Ladder::new(Glicko2::default(), 16.0);
+    ladder.add_results("strong", "weak", 8, 1, 1);
+    ladder.add_result("weak", "other", 0.5);
+    ladder.end_period();
+    ladder.add_result("strong", "other", 1.0);
+
+    let ranking = ladder.ranking();
+    assert_eq!(ranking.len(), 3);
+    assert_eq!(ranking[0].0, "strong");
+    assert_eq!(ranking[0].1.games, 11);
+    assert!(ladder.get("strong").unwrap().elo > ladder.get("weak").unwrap().elo);
+    assert!(ladder.get("missing").is_none());
+    assert_eq!(ladder.pending_results(), 1);
+
+    // json doesn't round-trip floats exactly
+    let loaded = Ladder::from_json(&ladder.to_json()).unwrap();
+    assert_eq!(loaded.pending_results(), 1);
+    for ((name, entry), (loaded_name, loaded_entry)) in ladder.ranking().iter().zip(loaded.ranking()) {
+        assert_eq!(*name, loaded_name);
+        assert_eq!(entry.games, loaded_entry.games);
+        assert!((entry.elo - loaded_entry.elo).abs() < 1e-9);
+        assert!((entry.glicko.rating - loaded_entry.glicko.rating).abs() < 1e-9);
+        assert!((entry.glicko.deviation - loaded_entry.glicko.deviation).abs() < 1e-9);
+    }
+
+    assert!(Ladder::from_json(&serde_json::json!({"tau": 0.5})).is_err());
+}
@@ -0,0 +1,73 @@
+use internal_iterator::InternalIterator;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::board::{Board, BoardAvailableMoves, Player};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::games::sttt::{Coord, STTTBoard};
+use board_game::util::render::{to_svg, to_terminal, visit_heatmap, Cell, RenderBoard};
+
+#[test]
+fn ataxx_svg() {
+    let board = AtaxxBoard::from_fen("x5o/7/2-1-2/7/2-1-2/7/o5x x 0 1");
+    assert_eq!(board.render_size(), (7, 7));
+    assert_eq!(board.render_cell(0, 0), Cell::Stone(Player::A));
+    assert_eq!(board.render_cell(0, 6), Cell::Stone(Player::B));
+    assert_eq!(board.render_cell(2, 2), Cell::Blocked);
+    assert_eq!(board.render_cell(3, 3), Cell::Empty);
+
+    let mv = Move::from_uai("a7c5");
+    assert_eq!(board.render_move_cells(mv), vec![(0, 0), (2, 2)]);
+    assert_eq!(board.render_move_cells(Move::Pass), vec![]);
+
+    let heatmap = [
+        (Move::from_uai("f1"), 0.5),
+        (Move::from_uai("a6"), 0.25),
+        (Move::Pass, 1.0),
+    ];
+    let svg = to_svg(&board, Some(Move::from_uai("b6")), &heatmap);
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<circle").count(), 4);
+    assert_eq!(svg.matches("#555555").count(), 4);
+    assert_eq!(svg.matches("#f6e27a").count(), 1);
+    // the pass has no cell, so the strongest heat is 0.5
+    assert!(svg.contains(r#"fill-opacity="0.800""#));
+    assert!(svg.contains(r#"fill-opacity="0.400""#));
+}
+
+#[test]
+fn sttt_svg() {
+    let mv = Coord::from_xy(4, 4);
+    let board = STTTBoard::default().clone_and_play(mv);
+    assert_eq!(board.render_cell(4, 4), Cell::Piece('X'));
+    assert_eq!(board.render_block_size(), Some(3));
+
+    let svg = to_svg(&board, Some(mv), &[]);
+    assert_eq!(svg.matches("<text").count(), 1);
+    assert_eq!(svg.matches("<line").count(), 8);
+    assert!(!svg.contains("fill-opacity"));
+}
+
+#[test]
+fn ataxx_visit_heatmap() {
+    let board = AtaxxBoard::default();
+    let mut bot = MCTSBot::new(500, 2.0, Xoroshiro64StarStar::seed_from_u64(0));
+    let tree = bot.build_tree(&board);
+
+    let heatmap = visit_heatmap(&tree);
+    assert_eq!(heatmap.len(), board.available_moves().count());
+    let total: f32 = heatmap.iter().map(|&(_, f)| f).sum();
+    assert!((total - 1.0).abs() < 1e-3);
+
+    let text = to_terminal(&board, &heatmap);
+    println!("{}", text);
+    // 7 rows and the listed moves
+    assert_eq!(text.lines().count(), 7 + 5);
+    assert!(text.contains(&format!("{:?}", tree.best_move())));
+
+    let svg = to_svg(&board, None, &heatmap);
+    assert!(svg.contains("fill-opacity=\"0.800\""));
+}
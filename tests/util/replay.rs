@@ -0,0 +1,101 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::util::replay::{parse_replays, Replay};
+
+#[test]
+fn ataxx_round_trip() {
+    let mut rng = Xoroshiro64StarStar::seed_from_u64(0);
+    let start = AtaxxBoard::from_fen("x5o/7/2-1-2/7/2-1-2/7/o5x o 0 1");
+
+    let mut replay = Replay::new(start.clone());
+    let mut board = start.clone();
+    while !board.is_done() {
+        let mv = board.random_available_move(&mut rng);
+        let annotations = vec![("ply".to_string(), replay.moves.len().to_string())];
+        replay.push(mv, annotations);
+        board.play(mv);
+    }
+    assert_eq!(replay.end(), board);
+
+    let boards = replay.boards();
+    assert_eq!(boards.len(), replay.moves.len() + 1);
+    assert_eq!(boards[0], start);
+    assert_eq!(boards.last(), Some(&board));
+
+    // write two games to the same file
+    let text = format!("{}\n# a comment\n{}", replay, Replay::new(AtaxxBoard::default()));
+    let parsed = parse_replays::<AtaxxBoard>(&text).unwrap();
+    assert_eq!(parsed, vec![replay.clone(), Replay::new(AtaxxBoard::default())]);
+    assert_eq!(parsed[0].moves[3].annotation("ply"), Some("3"));
+}
+
+#[test]
+fn parse() {
+    let input = "
+start x5o/7/7/7/7/7/o5x x 0 1
+f1 time=0.5 value=-0.25
+b1
+";
+    let replays = parse_replays::<AtaxxBoard>(input).unwrap();
+    assert_eq!(replays.len(), 1);
+
+    let moves = &replays[0].moves;
+    assert_eq!(moves.len(), 2);
+    assert_eq!(moves[0].mv, Move::from_uai("f1"));
+    assert_eq!(moves[0].annotation("value"), Some("-0.25"));
+    assert_eq!(moves[1].annotation("time"), None);
+    assert_eq!(replays[0].end().next_player(), Player::A);
+    assert_eq!(replays[0].end().outcome(), None::<Outcome>);
+}
+
+#[test]
+fn parse_invalid() {
+    let inputs = [
+        "f1",
+        "start invalid fen",
+        "start x5o/7/7/7/7/7/o5x x 0 1\nb1",
+        "start x5o/7/7/7/7/7/o5x x 0 1\nf1 time",
+        "start x5o/7/7/7/7/7/o5x x 0 1\nf1 =1",
+    ];
+    for input in inputs {
+        assert!(
+            parse_replays::<AtaxxBoard>(input).is_err(),
+            "'{}' should be invalid",
+            input
+        );
+    }
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn record_bot_game() {
+    use board_game::ai::simple::RandomBot;
+    use board_game::util::bot_game::run_observed;
+    use board_game::util::replay::ReplayRecorder;
+    use rand::thread_rng;
+
+    let recorder = ReplayRecorder::new(vec![]);
+    let result = run_observed(
+        AtaxxBoard::default,
+        || RandomBot::new(thread_rng()),
+        || RandomBot::new(thread_rng()),
+        2,
+        false,
+        |event| recorder.observe(event),
+    );
+
+    let output = String::from_utf8(recorder.finish().unwrap()).unwrap();
+    let replays = parse_replays::<AtaxxBoard>(&output).unwrap();
+    assert_eq!(replays.len(), 2);
+
+    let moves: usize = replays.iter().map(|r| r.moves.len()).sum();
+    assert_eq!(moves as f32, result.game_length * result.game_count as f32);
+    for replay in &replays {
+        assert!(replay.end().is_done());
+        assert_eq!(replay.moves[0].annotation("bot"), Some("l"));
+        assert!(replay.moves[0].annotation("time").is_some());
+    }
+}
@@ -0,0 +1,52 @@
+use board_game::board::Player;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::util::sgf::{parse_sgf, SgfNode};
+
+#[test]
+fn parse_variations() {
+    let input = "(;GM[1]C[escaped \\] bracket];B[aa];W[bb](;B[cc];W[dd])(;B[ee]))(;B[ff])";
+    let trees = parse_sgf(input).unwrap();
+    assert_eq!(trees.len(), 2);
+
+    let root = &trees[0];
+    assert_eq!(root.property("GM"), Some("1"));
+    assert_eq!(root.property("C"), Some("escaped ] bracket"));
+
+    let main_line: Vec<_> = root.main_line().filter_map(|n| n.move_value()).collect();
+    assert_eq!(
+        main_line,
+        vec![
+            (Player::A, "aa"),
+            (Player::B, "bb"),
+            (Player::A, "cc"),
+            (Player::B, "dd")
+        ]
+    );
+
+    let branch = &root.children[0].children[0];
+    assert_eq!(branch.children.len(), 2);
+    assert_eq!(branch.children[1].move_value(), Some((Player::A, "ee")));
+
+    // writing and parsing again gives the same tree
+    let written = root.to_string();
+    assert_eq!(parse_sgf(&written).unwrap(), vec![root.clone()]);
+}
+
+#[test]
+fn ataxx_game_with_variation() {
+    let start = AtaxxBoard::default();
+    let moves = [Move::from_uai("f1"), Move::from_uai("b1")];
+
+    let mut root = SgfNode::from_game(vec![], &start, &moves, |_, mv| mv.to_uai());
+    // add an alternative for the first move
+    root.push_line(&start, &[Move::from_uai("b7")], |_, mv| mv.to_uai());
+
+    let parsed = parse_sgf(&root.to_string()).unwrap();
+    assert_eq!(parsed, vec![root.clone()]);
+    assert_eq!(root.children.len(), 2);
+
+    let replayed = parsed[0]
+        .replay_main_line(&start, |_, s| Some(Move::from_uai(s)))
+        .unwrap();
+    assert_eq!(replayed, moves);
+}
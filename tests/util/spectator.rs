@@ -0,0 +1,42 @@
+#![cfg(feature = "native")]
+
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::util::bot_game::GameEvent;
+use board_game::util::spectator::TerminalSpectator;
+
+#[test]
+fn follow_game() {
+    let spectator = TerminalSpectator::new(Some(1), vec![]);
+    let start = AtaxxBoard::default();
+    let mv = Move::from_uai("f1");
+    let next = start.clone_and_play(mv);
+
+    for game_id in 0..2 {
+        spectator.observe(&GameEvent::Start { game_id, board: &start });
+        spectator.observe(&GameEvent::Move {
+            game_id,
+            board: &start,
+            mv,
+            left: game_id == 1,
+            time: 0.5,
+            value: Some(0.25),
+        });
+        spectator.observe(&GameEvent::End {
+            game_id,
+            board: &next,
+            outcome: Outcome::WonBy(Player::A),
+        });
+    }
+
+    let output = String::from_utf8(spectator.into_output()).unwrap();
+    let frames: Vec<&str> = output.split("\x1b[2J\x1b[H").skip(1).collect();
+
+    // only game 1 is drawn: start, move and end
+    assert_eq!(frames.len(), 3);
+    let last_frame = frames[2];
+    assert!(last_frame.contains("Game 1 finished"));
+    assert!(last_frame.contains("1.f1"));
+    assert!(last_frame.contains("left  (A): value +0.250, time 0.50s"));
+    assert!(last_frame.contains("Finished games: 2, left 1 / draw 0 / right 1"));
+}
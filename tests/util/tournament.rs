@@ -0,0 +1,127 @@
+#![cfg(feature = "native")]
+
+use rand::thread_rng;
+
+use board_game::ai::simple::{RandomBot, RolloutBot};
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::sttt::STTTBoard;
+use board_game::util::bot_game::MatchOptions;
+use board_game::util::elo::{fit_elo, fit_elo_anchored};
+use board_game::util::tournament::{gauntlet, round_robin, round_robin_with};
+
+#[test]
+fn fit_elo_symmetric() {
+    // a beats b 75% of the time, b beats c 75% of the time
+    let points = vec![vec![0.0, 75.0, 0.0], vec![25.0, 0.0, 75.0], vec![0.0, 25.0, 0.0]];
+    let games = vec![vec![0, 100, 0], vec![100, 0, 100], vec![0, 100, 0]];
+    let elo = fit_elo(&points, &games);
+
+    assert!(elo.iter().sum::<f32>().abs() < 0.01);
+    assert!(elo[1].abs() < 0.01);
+    // slightly less than the 191 elo of a 75% score because of the prior
+    assert!(elo[0] > 180.0 && elo[0] < 191.0, "{:?}", elo);
+    assert!((elo[0] + elo[2]).abs() < 0.01);
+}
+
+#[test]
+fn fit_elo_perfect_score() {
+    let points = vec![vec![0.0, 10.0], vec![0.0, 0.0]];
+    let games = vec![vec![0, 10], vec![10, 0]];
+    let elo = fit_elo(&points, &games);
+    assert!(elo.iter().all(|e| e.is_finite()));
+    assert!(elo[0] > elo[1]);
+}
+
+#[test]
+fn fit_elo_anchored_even() {
+    // scoring 50% against opponents at -100 and +100 is a rating in between
+    let elo = fit_elo_anchored(&[-100.0, 100.0], &[50.0, 50.0], &[100, 100]);
+    assert!(elo.abs() < 0.1, "{}", elo);
+
+    // a 75% score against a single opponent, slightly less than 191 elo above it because of the prior
+    let elo = fit_elo_anchored(&[1000.0, 0.0], &[75.0, 0.0], &[100, 0]);
+    assert!(elo > 1180.0 && elo < 1191.0, "{}", elo);
+
+    let elo = fit_elo_anchored(&[0.0], &[10.0], &[10]);
+    assert!(elo.is_finite() && elo > 0.0);
+}
+
+#[derive(Debug)]
+enum TestBot<R: rand::Rng> {
+    Random(RandomBot<R>),
+    Rollout(RolloutBot<R>),
+}
+
+impl<B: Board, R: rand::Rng + std::fmt::Debug> Bot<B> for TestBot<R> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        match self {
+            TestBot::Random(bot) => bot.select_move(board),
+            TestBot::Rollout(bot) => bot.select_move(board),
+        }
+    }
+}
+
+#[test]
+fn round_robin_sttt() {
+    let bots: Vec<Box<dyn Fn() -> TestBot<_> + Sync>> = vec![
+        Box::new(|| TestBot::Random(RandomBot::new(thread_rng()))),
+        Box::new(|| TestBot::Random(RandomBot::new(thread_rng()))),
+        Box::new(|| TestBot::Rollout(RolloutBot::new(50, thread_rng()))),
+    ];
+    let result = round_robin(&bots, STTTBoard::default, 5, true);
+    println!("{}", result);
+
+    assert_eq!(result.names.len(), 3);
+    for i in 0..3 {
+        assert_eq!(result.score(i, i), None);
+        for j in 0..3 {
+            if i != j {
+                assert_eq!(result.games(i, j), 10);
+                assert!((result.score(i, j).unwrap() + result.score(j, i).unwrap() - 1.0).abs() < 1e-6);
+            }
+        }
+    }
+    assert!(result.elo[2] > result.elo[0]);
+}
+
+#[test]
+fn round_robin_status_file() {
+    let path = std::env::temp_dir().join(format!("board_game_tournament_{}.json", std::process::id()));
+    let options = MatchOptions {
+        status_file: Some(path.clone()),
+        ..MatchOptions::default()
+    };
+    let bots = vec![|| RandomBot::new(thread_rng()), || RandomBot::new(thread_rng()), || {
+        RandomBot::new(thread_rng())
+    }];
+    let result = round_robin_with(&bots, STTTBoard::default, 2, false, &options);
+
+    let status: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(status["pairings"], 3);
+    assert_eq!(status["max_pairings"], 3);
+    assert_eq!(status["wins"], serde_json::json!(result.wins));
+    assert_eq!(status["names"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn gauntlet_sttt() {
+    let opponents = vec![|| RandomBot::new(thread_rng()), || RandomBot::new(thread_rng())];
+    let result = gauntlet(
+        || RolloutBot::new(50, thread_rng()),
+        &opponents,
+        &[0.0, 100.0],
+        STTTBoard::default,
+        5,
+        true,
+    );
+    println!("{}", result);
+
+    assert_eq!(result.opponents.len(), 2);
+    assert!(result.results.iter().all(|r| r.game_count == 10));
+    assert!(result.candidate.contains("RolloutBot"));
+    assert!(result.elo > 50.0, "{}", result.elo);
+    assert_eq!(result.to_json()["results"].as_array().unwrap().len(), 2);
+}
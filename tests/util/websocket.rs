@@ -0,0 +1,48 @@
+#![cfg(feature = "websocket")]
+
+use std::net::TcpStream;
+use std::time::Duration;
+
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::util::bot_game::GameEvent;
+use board_game::util::websocket::LiveBroadcaster;
+
+#[test]
+fn broadcast_event() {
+    let broadcaster = LiveBroadcaster::bind("127.0.0.1:0").unwrap();
+    let addr = broadcaster.local_addr();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let (mut client, _) = tungstenite::client(format!("ws://{}/", addr), stream).unwrap();
+
+    while broadcaster.client_count() == 0 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let board = AtaxxBoard::default();
+    broadcaster.send_event(&GameEvent::Start {
+        game_id: 3,
+        board: &board,
+    });
+
+    let message = client.read_message().unwrap().into_text().unwrap();
+    let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+    assert_eq!(message["type"], "start");
+    assert_eq!(message["game"], 3);
+    assert_eq!(message["fen"], board.to_fen());
+}
+
+#[test]
+fn stalled_handshake() {
+    let broadcaster = LiveBroadcaster::bind("127.0.0.1:0").unwrap();
+    let addr = broadcaster.local_addr();
+
+    // a client that connects but never sends the handshake must not block the next one
+    let _stalled = TcpStream::connect(addr).unwrap();
+    let stream = TcpStream::connect(addr).unwrap();
+    let (_client, _) = tungstenite::client(format!("ws://{}/", addr), stream).unwrap();
+
+    while broadcaster.client_count() == 0 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}